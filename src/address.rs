@@ -0,0 +1,455 @@
+use std::fmt;
+use std::str::FromStr;
+
+use failure::Fail;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+
+use encoding::{base58check_decode, base58check_encode, bech32_decode, bech32_encode,
+               convert_bits, Bech32Variant};
+use network::Network;
+
+const OP_0: u8 = 0x00;
+const OP_1: u8 = 0x51;
+const OP_DUP: u8 = 0x76;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_HASH160: u8 = 0xa9;
+const OP_CHECKSIG: u8 = 0xac;
+
+// Networks probed when decoding base58; regtest shares the testnet prefixes.
+const BASE58_NETWORKS: [Network; 4] = [
+    Network::ZcashMainnet,
+    Network::ZcashTestnet,
+    Network::BitcoinMainnet,
+    Network::BitcoinTestnet,
+];
+
+const SEGWIT_NETWORKS: [Network; 3] = [
+    Network::BitcoinMainnet,
+    Network::BitcoinTestnet,
+    Network::BitcoinRegtest,
+];
+
+#[derive(Debug, Display, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    #[display(fmt = "Invalid base58check encoding.")]
+    Base58,
+    #[display(fmt = "Invalid bech32 encoding.")]
+    Bech32,
+    #[display(fmt = "Unknown address prefix.")]
+    UnknownPrefix,
+    #[display(fmt = "Invalid address length.")]
+    InvalidLength,
+    #[display(fmt = "Invalid witness version {}.", _0)]
+    InvalidWitnessVersion(u8),
+    #[display(fmt = "Invalid witness program.")]
+    InvalidWitnessProgram,
+    #[display(fmt = "Address is not valid on {}.", _0)]
+    NetworkMismatch(Network),
+    #[display(fmt = "Invalid set of unified address receivers.")]
+    InvalidReceivers,
+}
+
+impl Fail for AddressError {}
+
+/// Data encoded in a transparent address.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Payload {
+    PubkeyHash([u8; 20]),
+    ScriptHash([u8; 20]),
+    WitnessProgram { version: u8, program: Vec<u8> },
+}
+
+/// Transparent Zcash address or Bitcoin P2PKH, P2SH or segwit address, validated offline.
+///
+/// Base58 addresses do not distinguish testnet from regtest; such addresses are
+/// reported as testnet and accepted on both by `is_valid_for`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Address {
+    network: Network,
+    payload: Payload,
+    encoded: String,
+}
+
+impl Address {
+    pub fn new(network: Network, payload: Payload) -> Result<Address, AddressError> {
+        let encoded = match payload {
+            Payload::PubkeyHash(ref hash) => encode_base58(network.p2pkh_prefix(), hash),
+            Payload::ScriptHash(ref hash) => encode_base58(network.p2sh_prefix(), hash),
+            Payload::WitnessProgram {
+                version,
+                ref program,
+            } => {
+                let hrp = network
+                    .segwit_hrp()
+                    .ok_or(AddressError::NetworkMismatch(network))?;
+                check_witness_program(version, program)?;
+                let variant = if version == 0 {
+                    Bech32Variant::Bech32
+                } else {
+                    Bech32Variant::Bech32m
+                };
+                let mut data = vec![version];
+                data.extend(convert_bits(program, 8, 5, true).unwrap());
+                bech32_encode(hrp, &data, variant)
+            }
+        };
+        Ok(Address {
+            network,
+            payload,
+            encoded,
+        })
+    }
+
+    pub fn p2pkh(network: Network, pubkey_hash: [u8; 20]) -> Address {
+        Address::new(network, Payload::PubkeyHash(pubkey_hash)).unwrap()
+    }
+
+    pub fn p2sh(network: Network, script_hash: [u8; 20]) -> Address {
+        Address::new(network, Payload::ScriptHash(script_hash)).unwrap()
+    }
+
+    /// Parses an address and checks that it belongs to `network`.
+    pub fn parse_for(s: &str, network: Network) -> Result<Address, AddressError> {
+        let address: Address = s.parse()?;
+        if address.is_valid_for(network) {
+            Ok(address)
+        } else {
+            Err(AddressError::NetworkMismatch(network))
+        }
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+
+    pub fn is_valid_for(&self, network: Network) -> bool {
+        match self.payload {
+            Payload::WitnessProgram { .. } => self.network == network,
+            _ => {
+                network.p2pkh_prefix() == self.network.p2pkh_prefix()
+                    && network.p2sh_prefix() == self.network.p2sh_prefix()
+            }
+        }
+    }
+
+    /// Returns the output script paying to this address.
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        match self.payload {
+            Payload::PubkeyHash(ref hash) => {
+                let mut script = vec![OP_DUP, OP_HASH160, 20];
+                script.extend_from_slice(hash);
+                script.extend_from_slice(&[OP_EQUALVERIFY, OP_CHECKSIG]);
+                script
+            }
+            Payload::ScriptHash(ref hash) => {
+                let mut script = vec![OP_HASH160, 20];
+                script.extend_from_slice(hash);
+                script.push(OP_EQUAL);
+                script
+            }
+            Payload::WitnessProgram {
+                version,
+                ref program,
+            } => {
+                let opcode = if version == 0 { OP_0 } else { OP_1 + version - 1 };
+                let mut script = vec![opcode, program.len() as u8];
+                script.extend_from_slice(program);
+                script
+            }
+        }
+    }
+}
+
+fn encode_base58(prefix: &[u8], hash: &[u8; 20]) -> String {
+    let mut data = prefix.to_vec();
+    data.extend_from_slice(hash);
+    base58check_encode(&data)
+}
+
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), AddressError> {
+    if version > 16 {
+        return Err(AddressError::InvalidWitnessVersion(version));
+    }
+    if program.len() < 2 || program.len() > 40 {
+        return Err(AddressError::InvalidWitnessProgram);
+    }
+    if version == 0 && program.len() != 20 && program.len() != 32 {
+        return Err(AddressError::InvalidWitnessProgram);
+    }
+    Ok(())
+}
+
+fn parse_segwit(s: &str) -> Option<Result<Address, AddressError>> {
+    let (hrp, data, variant) = bech32_decode(s)?;
+    let network = *SEGWIT_NETWORKS
+        .iter()
+        .find(|network| network.segwit_hrp() == Some(hrp.as_str()))?;
+
+    let parse = || {
+        let (&version, program) = data.split_first().ok_or(AddressError::InvalidWitnessProgram)?;
+        let expected = if version == 0 {
+            Bech32Variant::Bech32
+        } else {
+            Bech32Variant::Bech32m
+        };
+        if variant != expected {
+            return Err(AddressError::Bech32);
+        }
+        let program = convert_bits(program, 5, 8, false).ok_or(AddressError::InvalidWitnessProgram)?;
+        check_witness_program(version, &program)?;
+        Ok(Address {
+            network,
+            payload: Payload::WitnessProgram { version, program },
+            encoded: s.to_ascii_lowercase(),
+        })
+    };
+    Some(parse())
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Address, AddressError> {
+        if let Some(address) = parse_segwit(s) {
+            return address;
+        }
+
+        let data = base58check_decode(s).ok_or(AddressError::Base58)?;
+        for &network in &BASE58_NETWORKS {
+            let prefixes = [
+                (network.p2pkh_prefix(), true),
+                (network.p2sh_prefix(), false),
+            ];
+            for &(prefix, is_pubkey_hash) in &prefixes {
+                if !data.starts_with(prefix) {
+                    continue;
+                }
+                if data.len() != prefix.len() + 20 {
+                    return Err(AddressError::InvalidLength);
+                }
+                let mut hash = [0; 20];
+                hash.copy_from_slice(&data[prefix.len()..]);
+                let payload = if is_pubkey_hash {
+                    Payload::PubkeyHash(hash)
+                } else {
+                    Payload::ScriptHash(hash)
+                };
+                return Ok(Address {
+                    network,
+                    payload,
+                    encoded: s.to_owned(),
+                });
+            }
+        }
+        Err(AddressError::UnknownPrefix)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.encoded)
+    }
+}
+
+impl AsRef<str> for Address {
+    fn as_ref(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encoded)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Address, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Address, AddressError, Payload};
+    use network::Network;
+
+    // BIP 350 segwit address test vectors with their output scripts.
+    const VALID_SEGWIT: &[(&str, &str)] = &[
+        (
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+        ),
+        (
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+        ),
+        (
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+            "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+        ),
+        ("BC1SW50QGDZ25J", "6002751e"),
+        (
+            "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+            "5210751e76e8199196d454941c45d1b3a323",
+        ),
+        (
+            "tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy",
+            "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+        ),
+        (
+            "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c",
+            "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+        ),
+        (
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        ),
+    ];
+
+    #[test]
+    fn segwit_valid_vectors() {
+        for &(s, script) in VALID_SEGWIT {
+            let address: Address = s.parse().unwrap_or_else(|e| panic!("{}: {}", s, e));
+            assert_eq!(hex::encode(address.script_pubkey()), script);
+            assert_eq!(address.as_str(), s.to_ascii_lowercase());
+
+            let reencoded = Address::new(address.network(), address.payload().clone()).unwrap();
+            assert_eq!(reencoded, address);
+        }
+    }
+
+    #[test]
+    fn segwit_checksum_must_match_witness_version() {
+        let wrong_variant = [
+            // Bech32 checksums on witness v1+ programs.
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+            // Bech32m checksums on witness v0 programs.
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+            "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+        ];
+        for s in &wrong_variant {
+            assert_eq!(s.parse::<Address>(), Err(AddressError::Bech32), "{}", s);
+        }
+    }
+
+    #[test]
+    fn segwit_invalid_vectors() {
+        let cases = [
+            (
+                "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+                AddressError::InvalidWitnessVersion(17),
+            ),
+            ("bc1pw5dgrnzv", AddressError::InvalidWitnessProgram),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+                AddressError::InvalidWitnessProgram,
+            ),
+            ("BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P", AddressError::InvalidWitnessProgram),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+                AddressError::InvalidWitnessProgram,
+            ),
+            (
+                "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+                AddressError::InvalidWitnessProgram,
+            ),
+            ("bc1gmk9yu", AddressError::InvalidWitnessProgram),
+        ];
+        for &(s, ref error) in &cases {
+            assert_eq!(s.parse::<Address>().as_ref(), Err(error), "{}", s);
+        }
+
+        let undecodable = [
+            // Unknown hrp, invalid checksum character and mixed case.
+            "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut",
+            "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+        ];
+        for s in &undecodable {
+            assert!(s.parse::<Address>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn segwit_addresses_are_bitcoin_only() {
+        let program = vec![0; 20];
+        let payload = Payload::WitnessProgram {
+            version: 0,
+            program,
+        };
+        assert_eq!(
+            Address::new(Network::ZcashMainnet, payload.clone()),
+            Err(AddressError::NetworkMismatch(Network::ZcashMainnet))
+        );
+        let address = Address::new(Network::BitcoinRegtest, payload).unwrap();
+        assert!(address.as_str().starts_with("bcrt1q"));
+        assert!(address.is_valid_for(Network::BitcoinRegtest));
+        assert!(!address.is_valid_for(Network::BitcoinTestnet));
+    }
+
+    #[test]
+    fn base58_addresses() {
+        let address: Address = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap();
+        assert_eq!(address.network(), Network::BitcoinMainnet);
+        assert_eq!(
+            hex::encode(address.script_pubkey()),
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"
+        );
+
+        let hash = [0x5a; 20];
+        let prefixes = [
+            (Network::ZcashMainnet, "t1", "t3"),
+            (Network::ZcashTestnet, "tm", "t2"),
+            (Network::BitcoinMainnet, "1", "3"),
+            (Network::BitcoinTestnet, "m", "2"),
+        ];
+        for &(network, p2pkh, p2sh) in &prefixes {
+            let addresses = vec![
+                (Address::p2pkh(network, hash), p2pkh),
+                (Address::p2sh(network, hash), p2sh),
+            ];
+            for (address, prefix) in addresses {
+                assert!(address.as_str().starts_with(prefix), "{}", address);
+                assert_eq!(address.as_str().parse(), Ok(address.clone()));
+                assert_eq!(Address::parse_for(address.as_str(), network), Ok(address.clone()));
+            }
+        }
+
+        // Base58 testnet addresses are reported as testnet but valid on regtest too.
+        let regtest = Address::p2pkh(Network::ZcashRegtest, hash);
+        let testnet: Address = regtest.as_str().parse().unwrap();
+        assert_eq!(testnet.network(), Network::ZcashTestnet);
+        assert!(Address::parse_for(testnet.as_str(), Network::ZcashRegtest).is_ok());
+        assert_eq!(
+            Address::parse_for(testnet.as_str(), Network::ZcashMainnet),
+            Err(AddressError::NetworkMismatch(Network::ZcashMainnet))
+        );
+    }
+
+    #[test]
+    fn base58_invalid_addresses() {
+        assert_eq!(
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb".parse::<Address>(),
+            Err(AddressError::Base58)
+        );
+        // Valid checksum over a 21 byte payload with an unknown version byte.
+        let unknown = ::encoding::base58check_encode(&[0x30; 21]);
+        assert_eq!(unknown.parse::<Address>(), Err(AddressError::UnknownPrefix));
+        let short = ::encoding::base58check_encode(&[0x00; 20]);
+        assert_eq!(short.parse::<Address>(), Err(AddressError::InvalidLength));
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt;
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
+
+use failure::Fail;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::{Serialize, Serializer};
+
+/// Number of base units (satoshis or zatoshis) in one coin.
+pub const COIN: i64 = 100_000_000;
+
+const DECIMALS: usize = 8;
+
+/// Monetary amount stored as an integer number of satoshis (zatoshis on Zcash).
+///
+/// Amounts are serialized as the shortest JSON number that reads back as the same
+/// amount, the only form older nodes accept for every amount parameter. Amounts below
+/// 0.00001 use exponent notation, e.g. `1e-8` for one satoshi, which the nodes' fixed
+/// point parser accepts too. Any amount up to 2^26 coins, which covers every valid
+/// amount, survives the trip through a double exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const ONE_COIN: Amount = Amount(COIN);
+    pub const MAX_MONEY: Amount = Amount(21_000_000 * COIN);
+
+    pub const fn from_sat(sat: i64) -> Amount {
+        Amount(sat)
+    }
+
+    pub fn as_sat(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Returns `true` if the amount lies within `0..=MAX_MONEY`.
+    pub fn is_valid_money(self) -> bool {
+        self.0 >= 0 && self <= Amount::MAX_MONEY
+    }
+
+    pub fn checked_add(self, rhs: Amount) -> Option<Amount> {
+        self.0.checked_add(rhs.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Option<Amount> {
+        self.0.checked_sub(rhs.0).map(Amount)
+    }
+
+    pub fn checked_mul(self, rhs: i64) -> Option<Amount> {
+        self.0.checked_mul(rhs).map(Amount)
+    }
+
+    pub fn checked_div(self, rhs: i64) -> Option<Amount> {
+        self.0.checked_div(rhs).map(Amount)
+    }
+
+    pub fn checked_neg(self) -> Option<Amount> {
+        self.0.checked_neg().map(Amount)
+    }
+
+    pub fn checked_abs(self) -> Option<Amount> {
+        self.0.checked_abs().map(Amount)
+    }
+
+    // The node encodes amounts as JSON numbers with at most eight decimals.
+    // Every valid amount is far below 2^53 base units, so rounding the scaled
+    // double recovers the exact integer value.
+    fn from_json_f64(value: f64) -> Result<Amount, ParseAmountError> {
+        let sat = (value * COIN as f64).round();
+        if !sat.is_finite() || sat.abs() >= (1u64 << 53) as f64 {
+            return Err(ParseAmountError::TooBig);
+        }
+        Ok(Amount(sat as i64))
+    }
+}
+
+#[derive(Debug, Display, Clone, PartialEq, Eq)]
+pub enum ParseAmountError {
+    #[display(fmt = "Amount is empty.")]
+    Empty,
+    #[display(fmt = "Invalid character in amount: {:?}.", _0)]
+    InvalidCharacter(char),
+    #[display(fmt = "Amount has more than eight decimal places.")]
+    TooPrecise,
+    #[display(fmt = "Amount is out of range.")]
+    TooBig,
+}
+
+impl Fail for ParseAmountError {}
+
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    fn from_str(s: &str) -> Result<Amount, ParseAmountError> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (int_part, frac_part) = match digits.find('.') {
+            Some(pos) => (&digits[..pos], &digits[pos + 1..]),
+            None => (digits, ""),
+        };
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(ParseAmountError::Empty);
+        }
+        if frac_part.len() > DECIMALS {
+            return Err(ParseAmountError::TooPrecise);
+        }
+
+        let mut sat: i64 = 0;
+        let padding = DECIMALS - frac_part.len();
+        let all_digits = int_part
+            .chars()
+            .chain(frac_part.chars())
+            .chain(::std::iter::repeat_n('0', padding));
+        for c in all_digits {
+            let digit = c.to_digit(10).ok_or(ParseAmountError::InvalidCharacter(c))?;
+            sat = sat
+                .checked_mul(10)
+                .and_then(|sat| sat.checked_add(i64::from(digit)))
+                .ok_or(ParseAmountError::TooBig)?;
+        }
+        Ok(Amount(if negative { -sat } else { sat }))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let coin = COIN as u64;
+        write!(f, "{}{}.{:08}", sign, abs / coin, abs % coin)
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: Amount) -> Amount {
+        self.checked_add(rhs).expect("Amount addition overflow")
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, rhs: Amount) -> Amount {
+        self.checked_sub(rhs).expect("Amount subtraction overflow")
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        self.checked_neg().expect("Amount negation overflow")
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.0 as f64 / COIN as f64;
+        if Amount::from_json_f64(value) == Ok(*self) {
+            serializer.serialize_f64(value)
+        } else {
+            serializer.collect_str(self)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Amount, D::Error> {
+        struct AmountVisitor;
+
+        impl<'de> Visitor<'de> for AmountVisitor {
+            type Value = Amount;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a decimal amount as a number or a string")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Amount, E> {
+                v.checked_mul(COIN).map(Amount).ok_or_else(|| E::custom(ParseAmountError::TooBig))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Amount, E> {
+                if v > i64::MAX as u64 {
+                    return Err(E::custom(ParseAmountError::TooBig));
+                }
+                self.visit_i64(v as i64)
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Amount, E> {
+                Amount::from_json_f64(v).map_err(E::custom)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Amount, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+// Deserializes integer zatoshi fields such as `valueZat`.
+pub(crate) fn deserialize_sat<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    i64::deserialize(deserializer).map(Amount::from_sat)
+}
+
+pub(crate) fn deserialize_opt_sat<'de, D>(deserializer: D) -> Result<Option<Amount>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<i64>::deserialize(deserializer).map(|sat| sat.map(Amount::from_sat))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use super::{Amount, ParseAmountError, COIN};
+
+    #[test]
+    fn serializes_as_exact_numbers() {
+        let cases = [
+            (0, "0.0"),
+            (1, "1e-8"),
+            (10_000_000, "0.1"),
+            (COIN, "1.0"),
+            (-150_000_000, "-1.5"),
+            (12_345_678_901, "123.45678901"),
+            (Amount::MAX_MONEY.as_sat(), "21000000.0"),
+            (Amount::MAX_MONEY.as_sat() - 1, "20999999.99999999"),
+        ];
+        for &(sat, json) in &cases {
+            let amount = Amount::from_sat(sat);
+            assert_eq!(serde_json::to_string(&amount).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Amount>(json).unwrap(), amount);
+        }
+    }
+
+    #[test]
+    fn serializes_small_amounts_in_exponent_notation() {
+        let cases = [(1, "1e-8"), (99, "9.9e-7"), (999, "9.99e-6"), (1_000, "0.00001")];
+        for &(sat, json) in &cases {
+            let amount = Amount::from_sat(sat);
+            assert_eq!(serde_json::to_string(&amount).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Amount>(json).unwrap(), amount);
+        }
+        assert_eq!(serde_json::from_str::<Amount>("0.00000001").unwrap(), Amount::from_sat(1));
+    }
+
+    #[test]
+    fn round_trips_every_sat_near_the_edges() {
+        let edges = [0, COIN, Amount::MAX_MONEY.as_sat() - 1_000];
+        for &edge in &edges {
+            for sat in edge..edge + 1_000 {
+                let amount = Amount::from_sat(sat);
+                let json = serde_json::to_string(&amount).unwrap();
+                assert!(!json.starts_with('"'), "{} serialized as {}", sat, json);
+                assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+            }
+        }
+    }
+
+    #[test]
+    fn falls_back_to_strings_beyond_double_precision() {
+        let amount = Amount::from_sat(i64::MAX);
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, "\"92233720368.54775807\"");
+        assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+    }
+
+    #[test]
+    fn rounds_node_doubles_to_the_nearest_sat() {
+        let cases = [
+            ("0.30000000000000004", 30_000_000),
+            ("0.1", 10_000_000),
+            ("1.00000000499", COIN),
+            ("1.000000005", COIN + 1),
+            ("-0.00000001", -1),
+            ("1e-9", 0),
+            ("20999999.99999999", Amount::MAX_MONEY.as_sat() - 1),
+            ("3", 3 * COIN),
+        ];
+        for &(json, sat) in &cases {
+            assert_eq!(serde_json::from_str::<Amount>(json).unwrap(), Amount::from_sat(sat));
+        }
+        assert!(serde_json::from_str::<Amount>("1e300").is_err());
+    }
+
+    #[test]
+    fn parses_decimal_strings() {
+        assert_eq!("1.5".parse(), Ok(Amount::from_sat(150_000_000)));
+        assert_eq!("-.00000001".parse(), Ok(Amount::from_sat(-1)));
+        assert_eq!("+7".parse(), Ok(Amount::from_sat(7 * COIN)));
+        assert_eq!("".parse::<Amount>(), Err(ParseAmountError::Empty));
+        assert_eq!("0.000000001".parse::<Amount>(), Err(ParseAmountError::TooPrecise));
+        assert_eq!("1,5".parse::<Amount>(), Err(ParseAmountError::InvalidCharacter(',')));
+        assert_eq!("92233720369".parse::<Amount>(), Err(ParseAmountError::TooBig));
+        assert_eq!(Amount::from_sat(-1).to_string(), "-0.00000001");
+    }
+}
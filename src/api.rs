@@ -0,0 +1,586 @@
+// Typed RPC methods of `Client` and `AsyncClient`, expanded in their impl blocks with
+// `$Ret` as the return type: `Result` for `Client`, `RpcFuture` for `AsyncClient`. Both
+// provide `request` and `request_null`; the types named here must be in scope.
+macro_rules! rpc_methods {
+    ($Ret:ident) => {
+        pub fn getinfo(&self) -> $Ret<Info> {
+            self.request("getinfo", Vec::new())
+        }
+
+        pub fn getnewaddress(&self, account: &str) -> $Ret<String> {
+            self.request("getnewaddress", vec![Value::String(account.to_owned())])
+        }
+
+        pub fn getnewaddress_legacy(&self, account: &str) -> $Ret<String> {
+            self.request(
+                "getnewaddress",
+                vec![
+                    Value::String(account.to_owned()),
+                    Value::String("legacy".to_string()),
+                ],
+            )
+        }
+
+        pub fn z_getnewaddress(&self, kind: ShieldedAddressType) -> $Ret<ShieldedAddress> {
+            let params = json!([kind]).as_array().cloned().unwrap();
+            self.request("z_getnewaddress", params)
+        }
+
+        pub fn z_listaddresses(&self, include_watchonly: bool) -> $Ret<Vec<ShieldedAddress>> {
+            let params = json!([include_watchonly]).as_array().cloned().unwrap();
+            self.request("z_listaddresses", params)
+        }
+
+        pub fn validateaddress(&self, addr: &str) -> $Ret<ValidateAddressInfo> {
+            self.request("validateaddress", vec![Value::String(addr.to_owned())])
+        }
+
+        pub fn z_getnewaccount(&self) -> $Ret<NewAccount> {
+            self.request("z_getnewaccount", vec![])
+        }
+
+        /// Derives a unified address for `account`.
+        ///
+        /// Without `receiver_types` the node's default set of receivers is used.
+        pub fn z_getaddressforaccount(
+            &self,
+            account: u32,
+            receiver_types: Option<&[ReceiverType]>,
+            diversifier_index: Option<u64>,
+        ) -> $Ret<AccountAddress> {
+            let params = params::z_getaddressforaccount(account, receiver_types, diversifier_index);
+            self.request("z_getaddressforaccount", params)
+        }
+
+        pub fn z_listaccounts(&self) -> $Ret<Vec<AccountInfo>> {
+            self.request("z_listaccounts", vec![])
+        }
+
+        pub fn z_listunifiedreceivers<A: AsRef<str>>(&self, addr: A) -> $Ret<UnifiedReceivers> {
+            self.request("z_listunifiedreceivers", vec![Value::String(addr.as_ref().to_owned())])
+        }
+
+        pub fn z_getbalanceforaccount(
+            &self,
+            account: u32,
+            minconf: Option<u32>,
+        ) -> $Ret<AccountBalance> {
+            self.request("z_getbalanceforaccount", params::z_getbalanceforaccount(account, minconf))
+        }
+
+        pub fn z_validateaddress<A: AsRef<str>>(&self, addr: A) -> $Ret<ZValidateAddressInfo> {
+            self.request("z_validateaddress", vec![Value::String(addr.as_ref().to_owned())])
+        }
+
+        pub fn getaddressinfo(&self, addr: &str) -> $Ret<AddressInfo> {
+            self.request("getaddressinfo", vec![Value::String(addr.to_owned())])
+        }
+
+        pub fn createmultisig<V: AsRef<[String]>>(&self, signs: u8, addrs: V) -> $Ret<MultiSig> {
+            let n = serde_json::to_value(signs).unwrap();
+            let addrs = serde_json::to_value(addrs.as_ref()).unwrap();
+            self.request("createmultisig", vec![n, addrs])
+        }
+
+        pub fn sendtoaddress<A: AsRef<str>>(&self, addr: A, amount: Amount) -> $Ret<String> {
+            let params = vec![
+                serde_json::to_value(addr.as_ref()).unwrap(),
+                serde_json::to_value(amount).unwrap(),
+            ];
+            self.request("sendtoaddress", params)
+        }
+
+        /// Sends from `from` to several recipients. A missing fee is left to the node.
+        pub fn z_sendmany<A, R>(
+            &self,
+            from: A,
+            recipients: R,
+            minconf: u32,
+            fee: Option<Amount>,
+        ) -> $Ret<OperationId>
+        where
+            A: AsRef<str>,
+            R: AsRef<[SendManyRecipient]>,
+        {
+            let params = params::z_sendmany(from.as_ref(), recipients.as_ref(), minconf, fee);
+            self.request("z_sendmany", params)
+        }
+
+        pub fn z_getoperationstatus<V: AsRef<[OperationId]>>(
+            &self,
+            ids: V,
+        ) -> $Ret<Vec<OperationStatus>> {
+            let params = json!([ids.as_ref()]).as_array().cloned().unwrap();
+            self.request("z_getoperationstatus", params)
+        }
+
+        pub fn z_getoperationresult<V: AsRef<[OperationId]>>(
+            &self,
+            ids: V,
+        ) -> $Ret<Vec<OperationStatus>> {
+            let params = json!([ids.as_ref()]).as_array().cloned().unwrap();
+            self.request("z_getoperationresult", params)
+        }
+
+        pub fn z_listoperationids(&self, state: Option<OperationState>) -> $Ret<Vec<OperationId>> {
+            self.request("z_listoperationids", params::z_listoperationids(state))
+        }
+
+        pub fn getrawtransaction(&self, txid: &str) -> $Ret<String> {
+            let params = json!([txid, 0]).as_array().cloned().unwrap();
+            self.request("getrawtransaction", params)
+        }
+
+        /// Fetches a raw transaction and parses it offline.
+        pub fn getrawtransaction_tx(&self, txid: &str) -> $Ret<Transaction> {
+            let params = json!([txid, 0]).as_array().cloned().unwrap();
+            self.request("getrawtransaction", params)
+        }
+
+        pub fn getrawtransaction_verbose(&self, txid: &str) -> $Ret<RawTransactionInfo> {
+            let params = json!([txid, 1]).as_array().cloned().unwrap();
+            self.request("getrawtransaction", params)
+        }
+
+        pub fn getrawtransaction_verbose_zec(&self, txid: &str) -> $Ret<ZecRawTransactionInfo> {
+            let params = json!([txid, 1]).as_array().cloned().unwrap();
+            self.request("getrawtransaction", params)
+        }
+
+        pub fn createrawtransaction<I, O>(
+            &self,
+            transactions: I,
+            outputs: O,
+            data: Option<String>,
+        ) -> $Ret<String>
+        where
+            I: AsRef<[TransactionInput]>,
+            O: AsRef<[TransactionOutput]>,
+        {
+            let params =
+                params::createrawtransaction(transactions.as_ref(), outputs.as_ref(), data);
+            self.request("createrawtransaction", params)
+        }
+
+        pub fn dumpprivkey(&self, pub_key: &str) -> $Ret<String> {
+            let params = json!([pub_key]).as_array().cloned().unwrap();
+            self.request("dumpprivkey", params)
+        }
+
+        pub fn z_exportkey<A: AsRef<str>>(&self, addr: A) -> $Ret<SecretString> {
+            self.request("z_exportkey", vec![Value::String(addr.as_ref().to_owned())])
+        }
+
+        pub fn z_importkey(
+            &self,
+            key: &SecretString,
+            rescan: Rescan,
+            start_height: Option<u64>,
+        ) -> $Ret<()> {
+            self.request_null("z_importkey", params::import_key(key, rescan, start_height))
+        }
+
+        pub fn z_exportviewingkey<A: AsRef<str>>(&self, addr: A) -> $Ret<SecretString> {
+            self.request("z_exportviewingkey", vec![Value::String(addr.as_ref().to_owned())])
+        }
+
+        pub fn z_importviewingkey(
+            &self,
+            key: &SecretString,
+            rescan: Rescan,
+            start_height: Option<u64>,
+        ) -> $Ret<()> {
+            let params = params::import_key(key, rescan, start_height);
+            self.request_null("z_importviewingkey", params)
+        }
+
+        /// Writes all wallet keys to `filename` in the node's export directory and returns its
+        /// path.
+        pub fn z_exportwallet(&self, filename: &str) -> $Ret<String> {
+            self.request("z_exportwallet", vec![Value::String(filename.to_owned())])
+        }
+
+        pub fn z_importwallet(&self, filename: &str) -> $Ret<()> {
+            self.request_null("z_importwallet", vec![Value::String(filename.to_owned())])
+        }
+
+        /// Stores the decryption key in the node's memory for `timeout`.
+        pub fn walletpassphrase(&self, passphrase: &SecretString, timeout: Duration) -> $Ret<()> {
+            let params = json!([passphrase, timeout.as_secs()])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request_null("walletpassphrase", params)
+        }
+
+        pub fn walletlock(&self) -> $Ret<()> {
+            self.request_null("walletlock", vec![])
+        }
+
+        pub fn walletpassphrasechange(
+            &self,
+            old_passphrase: &SecretString,
+            new_passphrase: &SecretString,
+        ) -> $Ret<()> {
+            let params = json!([old_passphrase, new_passphrase])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request_null("walletpassphrasechange", params)
+        }
+
+        /// Encrypts the wallet; the node shuts down afterwards.
+        pub fn encryptwallet(&self, passphrase: &SecretString) -> $Ret<()> {
+            self.request_null("encryptwallet", vec![json!(passphrase)])
+        }
+
+        pub fn signrawtransaction<O, K>(
+            &self,
+            txhex: &str,
+            outputs: O,
+            priv_keys: K,
+        ) -> $Ret<SignTxOutput>
+        where
+            O: AsRef<[DependentOutput]>,
+            K: AsRef<[String]>,
+        {
+            let params = json!([txhex, outputs.as_ref(), priv_keys.as_ref()])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("signrawtransaction", params)
+        }
+
+        pub fn signrawtransaction_all<O, K>(
+            &self,
+            txhex: &str,
+            outputs: O,
+            priv_keys: K,
+        ) -> $Ret<SignTxOutput>
+        where
+            O: AsRef<[DependentOutput]>,
+            K: AsRef<[String]>,
+        {
+            let params = json!([
+                txhex,
+                outputs.as_ref(),
+                priv_keys.as_ref(),
+                "ALL".to_string()
+            ]).as_array()
+            .cloned()
+            .unwrap();
+            self.request("signrawtransaction", params)
+        }
+
+        pub fn sendrawtransaction(&self, txhex: &str) -> $Ret<String> {
+            self.request(
+                "sendrawtransaction",
+                vec![serde_json::to_value(txhex).unwrap()],
+            )
+        }
+
+        pub fn sendrawtransaction_tx(&self, tx: &Transaction) -> $Ret<String> {
+            self.sendrawtransaction(&tx.to_hex())
+        }
+
+        pub fn decoderawtransaction(&self, txhex: &str) -> $Ret<RawTransactionInfo> {
+            self.request(
+                "decoderawtransaction",
+                vec![serde_json::to_value(txhex).unwrap()],
+            )
+        }
+
+        pub fn decoderawtransaction_zec(&self, txhex: &str) -> $Ret<ZecRawTransactionInfo> {
+            self.request(
+                "decoderawtransaction",
+                vec![serde_json::to_value(txhex).unwrap()],
+            )
+        }
+
+        pub fn addwitnessaddress(&self, addr: &str) -> $Ret<String> {
+            self.request(
+                "addwitnessaddress",
+                vec![serde_json::to_value(addr).unwrap()],
+            )
+        }
+
+        pub fn listtransactions(
+            &self,
+            count: u32,
+            from: u32,
+            include_watch_only: bool,
+        ) -> $Ret<Vec<TransactionInfo>> {
+            let params = json!(["*", count, from, include_watch_only])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("listtransactions", params)
+        }
+
+        pub fn listunspent<V: AsRef<str> + Serialize>(
+            &self,
+            min_confirmations: u32,
+            max_confirmations: u32,
+            addresses: &[V],
+        ) -> $Ret<Vec<UnspentTransactionInfo>> {
+            let params = json!([min_confirmations, max_confirmations, addresses])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("listunspent", params)
+        }
+
+        pub fn listunspent_zcash<V: AsRef<str> + Serialize>(
+            &self,
+            min_confirmations: u32,
+            max_confirmations: u32,
+            addresses: &[V],
+        ) -> $Ret<Vec<UnspentTransactionInfoZec>> {
+            let params = json!([min_confirmations, max_confirmations, addresses])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("listunspent", params)
+        }
+
+        pub fn z_listunspent<V: AsRef<str> + Serialize>(
+            &self,
+            min_confirmations: u32,
+            max_confirmations: u32,
+            include_watchonly: bool,
+            addresses: &[V],
+        ) -> $Ret<Vec<ShieldedNote>> {
+            let params = json!([
+                min_confirmations,
+                max_confirmations,
+                include_watchonly,
+                addresses
+            ]).as_array()
+            .cloned()
+            .unwrap();
+            self.request("z_listunspent", params)
+        }
+
+        pub fn z_listreceivedbyaddress<A: AsRef<str>>(
+            &self,
+            addr: A,
+            minconf: u64,
+        ) -> $Ret<Vec<ShieldedNote>> {
+            let params = json!([addr.as_ref(), minconf]).as_array().cloned().unwrap();
+            self.request("z_listreceivedbyaddress", params)
+        }
+
+        pub fn importaddress<A: AsRef<str>>(
+            &self,
+            addr: A,
+            label: &str,
+            rescan: bool,
+            p2sh: bool,
+        ) -> $Ret<()> {
+            let params = json!([addr.as_ref(), label, rescan, p2sh])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request_null("importaddress", params)
+        }
+
+        pub fn importaddress_zcash<A: AsRef<str>>(
+            &self,
+            addr: A,
+            label: &str,
+            rescan: bool,
+        ) -> $Ret<()> {
+            let params = json!([addr.as_ref(), label, rescan])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request_null("importaddress", params)
+        }
+
+        /// Shields transparent coinbase outputs of `from` (`"*"` for all addresses).
+        ///
+        /// A missing limit uses the node default of 50 UTXOs. A missing fee is left to the node,
+        /// unless a limit is given: `ZCASH_DEFAULT_FEE` is then sent, since zcashd before 5.5
+        /// rejects a null fee.
+        /// Wait for the returned operation with `wait_for_operation`.
+        pub fn z_shieldcoinbase<F, A>(
+            &self,
+            from: F,
+            to: A,
+            fee: Option<Amount>,
+            limit: Option<u32>,
+        ) -> $Ret<ShieldCoinbaseResult>
+        where
+            F: AsRef<str>,
+            A: AsRef<str>,
+        {
+            let params = params::z_shieldcoinbase(from.as_ref(), to.as_ref(), fee, limit);
+            self.request("z_shieldcoinbase", params)
+        }
+
+        /// Merges UTXOs and notes of `from_addresses` into a single output to `to`.
+        ///
+        /// Missing limits use the node defaults of 50 UTXOs and 20 notes. A missing fee is left
+        /// to the node, unless a later argument is given: `ZCASH_DEFAULT_FEE` is then sent, since
+        /// zcashd before 5.5 rejects a null fee.
+        /// Wait for the returned operation with `wait_for_operation`.
+        pub fn z_mergetoaddress<V, A>(
+            &self,
+            from_addresses: &[V],
+            to: A,
+            fee: Option<Amount>,
+            utxo_limit: Option<u32>,
+            note_limit: Option<u32>,
+            memo: Option<&str>,
+        ) -> $Ret<MergeToAddressResult>
+        where
+            V: AsRef<str> + Serialize,
+            A: AsRef<str>,
+        {
+            let params = params::z_mergetoaddress(
+                from_addresses,
+                to.as_ref(),
+                fee,
+                utxo_limit,
+                note_limit,
+                memo,
+            );
+            self.request("z_mergetoaddress", params)
+        }
+
+        pub fn generate(&self, nblocks: u64, maxtries: u64) -> $Ret<Vec<String>> {
+            let params = json!([nblocks, maxtries]).as_array().cloned().unwrap();
+            self.request("generate", params)
+        }
+
+        pub fn generatetoaddress(
+            &self,
+            nblocks: u64,
+            addr: &str,
+            maxtries: u64,
+        ) -> $Ret<Vec<String>> {
+            let params = json!([nblocks, addr, maxtries])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("generatetoaddress", params)
+        }
+
+        pub fn stop(&self) -> $Ret<String> {
+            self.request("stop", vec![])
+        }
+
+        pub fn getreceivedbyaddress(&self, addr: &str, minconf: u64) -> $Ret<Amount> {
+            let params = json!([addr, minconf]).as_array().cloned().unwrap();
+            self.request("getreceivedbyaddress", params)
+        }
+
+        pub fn z_getbalance<A: AsRef<str>>(&self, addr: A, minconf: u64) -> $Ret<Amount> {
+            let params = json!([addr.as_ref(), minconf]).as_array().cloned().unwrap();
+            self.request("z_getbalance", params)
+        }
+
+        pub fn z_gettotalbalance(
+            &self,
+            minconf: u64,
+            include_watchonly: bool,
+        ) -> $Ret<TotalBalance> {
+            let params = json!([minconf, include_watchonly])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("z_gettotalbalance", params)
+        }
+
+        pub fn gettransaction(&self, txid: &str) -> $Ret<WalletTransactionInfo> {
+            let params = json!([txid]).as_array().cloned().unwrap();
+            self.request("gettransaction", params)
+        }
+
+        pub fn gettransaction_zec(&self, txid: &str) -> $Ret<ZecWalletTransactionInfo> {
+            let params = json!([txid]).as_array().cloned().unwrap();
+            self.request("gettransaction", params)
+        }
+
+        pub fn getblockchaininfo(&self) -> $Ret<BlockchainInfo> {
+            self.request("getblockchaininfo", vec![])
+        }
+
+        pub fn getblockheader<S: AsRef<str>>(&self, hash: S) -> $Ret<String> {
+            let params = json!([hash.as_ref(), false]).as_array().cloned().unwrap();
+            self.request("getblockheader", params)
+        }
+
+        pub fn getblockheader_verbose<S: AsRef<str>>(&self, hash: S) -> $Ret<BlockHeaderInfo> {
+            let params = json!([hash.as_ref(), true]).as_array().cloned().unwrap();
+            self.request("getblockheader", params)
+        }
+
+        pub fn getchaintips(&self) -> $Ret<Vec<ChainTip>> {
+            self.request("getchaintips", vec![])
+        }
+
+        pub fn getdifficulty(&self) -> $Ret<f64> {
+            self.request("getdifficulty", vec![])
+        }
+
+        /// Scans the whole UTXO set, which takes a while on mainnet.
+        pub fn gettxoutsetinfo(&self) -> $Ret<TxOutSetInfo> {
+            self.request("gettxoutsetinfo", vec![])
+        }
+
+        pub fn getblockcount(&self) -> $Ret<u64> {
+            self.request("getblockcount", vec![])
+        }
+
+        pub fn getbestblockhash(&self) -> $Ret<String> {
+            self.request("getbestblockhash", vec![])
+        }
+
+        pub fn getblockhash(&self, height: u64) -> $Ret<String> {
+            let params = json!([height]).as_array().cloned().unwrap();
+            self.request("getblockhash", params)
+        }
+
+        pub fn getblock<S: AsRef<str> + Serialize>(&self, hash: S) -> $Ret<String> {
+            let params = json!([hash.as_ref(), 0]).as_array().cloned().unwrap();
+            self.request("getblock", params)
+        }
+
+        pub fn getblock_verbose<S: AsRef<str>>(&self, hash: S) -> $Ret<BlockInfo> {
+            let params = json!([hash.as_ref(), 1]).as_array().cloned().unwrap();
+            self.request("getblock", params)
+        }
+
+        pub fn getblock_full<S: AsRef<str>>(&self, hash: S) -> $Ret<BlockInfo<RawTransactionInfo>> {
+            let params = json!([hash.as_ref(), 2]).as_array().cloned().unwrap();
+            self.request("getblock", params)
+        }
+
+        pub fn getblock_full_zec<S: AsRef<str>>(
+            &self,
+            hash: S,
+        ) -> $Ret<BlockInfo<ZecRawTransactionInfo>> {
+            let params = json!([hash.as_ref(), 2]).as_array().cloned().unwrap();
+            self.request("getblock", params)
+        }
+
+        pub fn decodescript(&self, script: &str) -> $Ret<ScriptInfo> {
+            let params = json!([script]).as_array().cloned().unwrap();
+            self.request("decodescript", params)
+        }
+
+        pub fn listreceivedbyaddress(
+            &self,
+            confirmations: u64,
+            include_empty: bool,
+            watch_only: bool,
+        ) -> $Ret<Vec<ReceivedByAddress>> {
+            let params = json!([confirmations, include_empty, watch_only])
+                .as_array()
+                .cloned()
+                .unwrap();
+            self.request("listreceivedbyaddress", params)
+        }
+    };
+}
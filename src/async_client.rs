@@ -0,0 +1,667 @@
+//! Non-blocking client built on futures, with a default transport on `reqwest`'s async API.
+//!
+//! The futures need a tokio runtime to run on. Retries and operation polling wait
+//! with tokio timers instead of blocking a thread.
+
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use exonum_jsonrpc::{Request, Response};
+use futures::future::{self, Loop};
+use futures::{Future, Stream};
+use reqwest::async::{Client as HttpClient, Response as HttpResponse};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::value::Value;
+use tokio_timer::Delay;
+
+use auth::Credentials;
+use batch::{private, Batch, BatchClient, BatchResponse};
+use method::{method_params, RpcMethod};
+use params;
+use redact::Redactor;
+use transport::parse_response;
+use super::{operation_txid, trace_request, AccountAddress, AccountBalance, AccountInfo, AddressInfo,
+            Amount, BlockHeaderInfo, BlockInfo, BlockchainInfo, ChainTip, DependentOutput, Error,
+            Info, MergeToAddressResult, MultiSig, Network, NewAccount, OperationId, OperationState,
+            OperationStatus, Params, RawTransactionInfo, ReceivedByAddress, ReceiverType, Redaction,
+            Rescan, RetryPolicy, RpcError, ScriptInfo, SecretString, SendManyRecipient,
+            ShieldCoinbaseResult, ShieldedAddress, ShieldedAddressType, ShieldedNote, SignTxOutput,
+            TotalBalance, Transaction, TransactionInfo, TransactionInput, TransactionOutput,
+            TxOutSetInfo, UnifiedReceivers, UnspentTransactionInfo, UnspentTransactionInfoZec,
+            ValidateAddressInfo, WalletTransactionInfo, ZValidateAddressInfo, ZecRawTransactionInfo,
+            ZecWalletTransactionInfo};
+
+/// Result of an `AsyncClient` call.
+pub type RpcFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// Delivers JSON-RPC requests to the node without blocking, like `Transport` does for
+/// `Client`.
+///
+/// The futures may be polled on any thread of the runtime, hence the `Send + Sync` bound.
+pub trait AsyncTransport: Send + Sync + 'static {
+    fn send_request(&self, request: &Request) -> RpcFuture<Response>;
+
+    /// Sends requests in a single round trip; responses may come in any order.
+    ///
+    /// The default implementation sends them as separate requests.
+    fn send_batch(&self, requests: &[Request]) -> RpcFuture<Vec<Response>> {
+        let responses = requests
+            .iter()
+            .map(|request| self.send_request(request))
+            .collect::<Vec<_>>();
+        Box::new(future::join_all(responses))
+    }
+}
+
+/// JSON-RPC over HTTP with `reqwest`'s async client, the default `AsyncTransport`.
+///
+/// Behaves like `HttpTransport`, including re-reading a rotated cookie.
+#[derive(Clone)]
+pub struct AsyncHttpTransport {
+    url: String,
+    credentials: Credentials,
+    http: HttpClient,
+}
+
+impl AsyncHttpTransport {
+    pub fn new<S>(url: S, user: Option<String>, password: Option<String>) -> AsyncHttpTransport
+    where
+        S: Into<String>,
+    {
+        AsyncHttpTransport::with_credentials(url, Credentials::new(user, password))
+    }
+
+    pub(crate) fn with_credentials<S>(url: S, credentials: Credentials) -> AsyncHttpTransport
+    where
+        S: Into<String>,
+    {
+        AsyncHttpTransport {
+            url: url.into(),
+            credentials,
+            http: HttpClient::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Password the transport was created with; for cookie authentication, the
+    /// cookie read at creation.
+    pub fn password(&self) -> &Option<String> {
+        self.credentials.password()
+    }
+
+    pub fn username(&self) -> &Option<String> {
+        self.credentials.user()
+    }
+
+    // Posts `body`, re-reading a rotated cookie once if the node rejects the credentials.
+    fn post<B, R>(&self, body: &B) -> RpcFuture<R>
+    where
+        B: Serialize,
+        R: DeserializeOwned + Send + 'static,
+    {
+        let body = match serde_json::to_vec(body) {
+            Ok(body) => body,
+            Err(e) => return Box::new(future::err(Error::from(RpcError::from(e)))),
+        };
+        let transport = self.clone();
+        Box::new(
+            self.send(body.clone())
+                .and_then(move |response| -> RpcFuture<HttpResponse> {
+                    if response.status() == StatusCode::UNAUTHORIZED
+                        && transport.credentials.reload_cookie()
+                    {
+                        transport.send(body)
+                    } else {
+                        Box::new(future::ok(response))
+                    }
+                })
+                .and_then(|response| {
+                    let status = response.status();
+                    response
+                        .into_body()
+                        .concat2()
+                        .map_err(|e| Error::from(RpcError::from(e)))
+                        .and_then(move |body| parse_response(status, &body))
+                }),
+        )
+    }
+
+    fn send(&self, body: Vec<u8>) -> RpcFuture<HttpResponse> {
+        let mut builder = self
+            .http
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json");
+        if let Some((user, password)) = self.credentials.basic_auth() {
+            builder = builder.basic_auth(user, password);
+        }
+        Box::new(
+            builder
+                .body(body)
+                .send()
+                .map_err(|e| Error::from(RpcError::from(e))),
+        )
+    }
+}
+
+impl fmt::Debug for AsyncHttpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AsyncHttpTransport")
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl AsyncTransport for AsyncHttpTransport {
+    fn send_request(&self, request: &Request) -> RpcFuture<Response> {
+        let id = request.id.clone();
+        Box::new(self.post(request).and_then(move |response: Response| {
+            if response.id != id {
+                return Err(RpcError::NonceMismatch.into());
+            }
+            Ok(response)
+        }))
+    }
+
+    fn send_batch(&self, requests: &[Request]) -> RpcFuture<Vec<Response>> {
+        self.post(&requests)
+    }
+}
+
+struct Inner<T> {
+    transport: Arc<T>,
+    nonce: AtomicUsize,
+    network: Mutex<Option<Network>>,
+    redactor: Redactor,
+    retry: RetryPolicy,
+}
+
+// Cloned when a shared client is configured further.
+impl<T> Clone for Inner<T> {
+    fn clone(&self) -> Inner<T> {
+        Inner {
+            transport: Arc::clone(&self.transport),
+            nonce: AtomicUsize::new(self.nonce.load(Ordering::Relaxed)),
+            network: Mutex::new(*self.network.lock().unwrap()),
+            redactor: self.redactor.clone(),
+            retry: self.retry.clone(),
+        }
+    }
+}
+
+/// Asynchronous counterpart of `Client` with the same typed methods.
+///
+/// Cloning is cheap; clones share the transport and settings. Configuring a clone with
+/// the `with_*` methods gives it its own copy of the settings.
+pub struct AsyncClient<T = AsyncHttpTransport> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Clone for AsyncClient<T> {
+    fn clone(&self) -> AsyncClient<T> {
+        AsyncClient {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AsyncClient<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AsyncClient")
+            .field("transport", &self.inner.transport)
+            .finish()
+    }
+}
+
+impl AsyncClient {
+    pub fn new<S>(url: S, user: Option<String>, password: Option<String>) -> AsyncClient
+    where
+        S: Into<String>,
+    {
+        AsyncClient::with_transport(AsyncHttpTransport::new(url, user, password))
+    }
+
+    pub fn url(&self) -> &str {
+        self.inner.transport.url()
+    }
+    pub fn password(&self) -> &Option<String> {
+        self.inner.transport.password()
+    }
+    pub fn username(&self) -> &Option<String> {
+        self.inner.transport.username()
+    }
+}
+
+impl<T: AsyncTransport> AsyncClient<T> {
+    /// Creates a client sending requests through `transport`.
+    pub fn with_transport(transport: T) -> AsyncClient<T> {
+        AsyncClient {
+            inner: Arc::new(Inner {
+                transport: Arc::new(transport),
+                nonce: AtomicUsize::new(0),
+                network: Mutex::new(None),
+                redactor: Redactor::default(),
+                retry: RetryPolicy::none(),
+            }),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.inner.transport
+    }
+
+    /// Repeats calls failing with transient errors, e.g. while the node is warming up.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> AsyncClient<T> {
+        self.inner_mut().retry = retry;
+        self
+    }
+
+    /// Masks parts of `method` calls in trace logs, in addition to the built-in
+    /// private key and passphrase methods.
+    pub fn with_redaction<S>(mut self, method: S, redaction: Redaction) -> AsyncClient<T>
+    where
+        S: Into<String>,
+    {
+        self.inner_mut().redactor.insert(method.into(), redaction);
+        self
+    }
+
+    /// Sets the network instead of detecting it from the node.
+    pub fn with_network(mut self, network: Network) -> AsyncClient<T> {
+        *self.inner_mut().network.get_mut().unwrap() = Some(network);
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner<T> {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Returns the network of the node, detected with `getblockchaininfo` on first use.
+    pub fn network(&self) -> RpcFuture<Network> {
+        if let Some(network) = *self.inner.network.lock().unwrap() {
+            return Box::new(future::ok(network));
+        }
+        let inner = Arc::clone(&self.inner);
+        Box::new(
+            self.getblockchaininfo()
+                .and_then(|info| info.network().ok_or(Error::UnknownNetwork(info.chain)))
+                .map(move |network| {
+                    *inner.network.lock().unwrap() = Some(network);
+                    network
+                }),
+        )
+    }
+
+    /// Calls any RPC method, with the same authentication, logging, retries and
+    /// error mapping as the typed methods.
+    pub fn call<R>(&self, method: &str, params: Params) -> RpcFuture<R>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        self.request(method, params)
+    }
+
+    pub fn call_method<M>(&self, params: M::Params) -> RpcFuture<M::Response>
+    where
+        M: RpcMethod,
+        M::Response: Send + 'static,
+    {
+        match method_params::<M>(&params) {
+            Ok(params) => self.request(M::NAME, params),
+            Err(e) => Box::new(future::err(e)),
+        }
+    }
+
+    pub fn batch(&self) -> Batch<'_, AsyncClient<T>> {
+        Batch::new(self)
+    }
+
+    fn request<R>(&self, method: &str, params: Params) -> RpcFuture<R>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        let method = method.to_owned();
+        Box::new(future::loop_fn(1, move |attempt| {
+            let retry = Arc::clone(&inner);
+            let method = method.clone();
+            inner
+                .request_once::<R>(&method, params.clone())
+                .then(move |result| -> RpcFuture<Loop<R, u32>> {
+                    match result {
+                        Err(ref e) if retry.retry.should_retry(&method, attempt, e) => {
+                            let delay = retry.retry.delay(attempt);
+                            debug!("{} failed: {}, retrying in {:?}", method, e, delay);
+                            Box::new(sleep(delay).map(move |_| Loop::Continue(attempt + 1)))
+                        }
+                        result => Box::new(future::result(result.map(Loop::Break))),
+                    }
+                })
+        }))
+    }
+
+    // For methods answering `{"result":null}` on success.
+    fn request_null(&self, method: &str, params: Params) -> RpcFuture<()> {
+        Box::new(
+            self.request::<Option<Value>>(method, params)
+                .then(|result| match result {
+                    Ok(_) | Err(Error::Rpc(RpcError::NoErrorOrResult)) => Ok(()),
+                    Err(e) => Err(e),
+                }),
+        )
+    }
+}
+
+impl<T: AsyncTransport> Inner<T> {
+    fn build_request(&self, method: &str, params: Params) -> Request {
+        let nonce = self.nonce.fetch_add(1, Ordering::Relaxed) + 1;
+        Request {
+            method: method.to_owned(),
+            params,
+            id: json!(nonce),
+        }
+    }
+
+    fn request_once<R>(self: &Arc<Self>, method: &str, params: Params) -> RpcFuture<R>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let request = self.build_request(method, params);
+        let inner = Arc::clone(self);
+        Box::new(
+            self.transport
+                .send_request(&request)
+                .and_then(move |response| {
+                    if response.id != request.id {
+                        return Err(Error::Rpc(RpcError::NonceMismatch));
+                    }
+                    trace_request(&inner.redactor, &request, &response);
+                    response.into_result::<R>().map_err(Error::from)
+                }),
+        )
+    }
+}
+
+fn sleep(duration: Duration) -> RpcFuture<()> {
+    Box::new(
+        Delay::new(Instant::now() + duration)
+            .map_err(|e| Error::Other(io::Error::other(e.to_string()))),
+    )
+}
+
+impl<T: AsyncTransport> private::Sealed for AsyncClient<T> {}
+
+impl<T: AsyncTransport> BatchClient for AsyncClient<T> {
+    fn build_request(&self, method: &str, params: Params) -> Request {
+        self.inner.build_request(method, params)
+    }
+}
+
+impl<'a, T: AsyncTransport> Batch<'a, AsyncClient<T>> {
+    pub fn send(self) -> RpcFuture<BatchResponse> {
+        if self.requests.is_empty() {
+            return Box::new(future::ok(BatchResponse::default()));
+        }
+
+        let inner = Arc::clone(&self.client.inner);
+        let requests = self.requests;
+        Box::new(
+            inner
+                .transport
+                .send_batch(&requests)
+                .map(move |received| BatchResponse::collect(&requests, received, &inner.redactor)),
+        )
+    }
+}
+
+// public api part
+impl<T: AsyncTransport> AsyncClient<T> {
+    rpc_methods!(RpcFuture);
+
+    /// Polls an asynchronous operation until it finishes and returns the resulting txid.
+    ///
+    /// Same as `Client::wait_for_operation`, waiting between polls with a timer.
+    pub fn wait_for_operation(
+        &self,
+        id: &OperationId,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> RpcFuture<String> {
+        let client = self.clone();
+        let id = id.clone();
+        let started = Instant::now();
+        Box::new(future::loop_fn((), move |()| {
+            let ids = [id.clone()];
+            let client = client.clone();
+            let id = id.clone();
+            find_status(client.z_getoperationstatus(&ids), &id).and_then(
+                move |status| -> RpcFuture<Loop<String, ()>> {
+                    if status.status.is_finished() {
+                        return Box::new(
+                            client
+                                .z_getoperationresult(&ids)
+                                .and_then(move |statuses| {
+                                    let status = statuses
+                                        .into_iter()
+                                        .find(|result| result.id == id)
+                                        .unwrap_or(status);
+                                    operation_txid(&id, status)
+                                })
+                                .map(Loop::Break),
+                        );
+                    }
+                    if started.elapsed() >= timeout {
+                        return Box::new(future::err(Error::OperationTimeout(id)));
+                    }
+                    Box::new(sleep(poll_interval).map(Loop::Continue))
+                },
+            )
+        }))
+    }
+}
+
+// Picks the status of `id` out of a `z_getoperationstatus` or `z_getoperationresult` answer.
+fn find_status(
+    statuses: RpcFuture<Vec<OperationStatus>>,
+    id: &OperationId,
+) -> RpcFuture<OperationStatus> {
+    let id = id.clone();
+    Box::new(statuses.and_then(move |statuses| {
+        statuses
+            .into_iter()
+            .find(|status| status.id == id)
+            .ok_or_else(|| Error::NoInformation(format!("Unknown operation {}", id)))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use exonum_jsonrpc::{Request, Response};
+    use futures::Future;
+    use serde_json::Value;
+    use tokio::runtime::current_thread::Runtime;
+
+    use super::{AsyncClient, AsyncTransport, RpcFuture};
+    use mock::{MockError, MockNode};
+    use {Error, Network, OperationId, RetryPolicy, RpcError};
+
+    // Answers like `node`, with the response ids replaced by `id`.
+    struct Renumbered {
+        node: MockNode,
+        id: Value,
+    }
+
+    impl AsyncTransport for Renumbered {
+        fn send_request(&self, request: &Request) -> RpcFuture<Response> {
+            let id = self.id.clone();
+            Box::new(self.node.send_request(request).map(|response| Response { id, ..response }))
+        }
+    }
+
+    // Uses the default `send_batch`.
+    struct Sequential(MockNode);
+
+    impl AsyncTransport for Sequential {
+        fn send_request(&self, request: &Request) -> RpcFuture<Response> {
+            AsyncTransport::send_request(&self.0, request)
+        }
+    }
+
+    // Runs futures that wait on timers.
+    fn run<T>(future: RpcFuture<T>) -> Result<T, Error> {
+        Runtime::new().unwrap().block_on(future)
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy::default()
+            .max_attempts(3)
+            .backoff(Duration::from_millis(1), Duration::from_millis(1))
+            .jitter(false)
+    }
+
+    #[test]
+    fn clones_can_be_configured() {
+        let client = AsyncClient::new("http://127.0.0.1:8232", Some("user".into()), None);
+        let shared = client.clone();
+        let configured = shared
+            .with_retry_policy(RetryPolicy::default())
+            .with_network(Network::ZcashRegtest);
+        assert_eq!(configured.username(), &Some("user".to_owned()));
+        assert_eq!(*configured.inner.network.lock().unwrap(), Some(Network::ZcashRegtest));
+        assert_eq!(*client.inner.network.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn sends_requests_through_the_transport() {
+        let node = MockNode::new();
+        node.respond("getblockhash", json!("00ff"));
+        node.respond("walletlock", Value::Null);
+        node.fail("getblockcount", -8, "Block height out of range");
+        let client = node.async_client();
+
+        assert_eq!(client.getblockhash(7).wait().unwrap(), "00ff");
+        client.walletlock().wait().unwrap();
+        match client.getblockcount().wait() {
+            Err(Error::InvalidParameter(ref msg)) => assert_eq!(msg, "Block height out of range"),
+            other => panic!("unexpected {:?}", other),
+        }
+        let calls = node.calls();
+        let ids: Vec<_> = calls.iter().map(|call| call.id.clone()).collect();
+        assert_eq!(ids, vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(calls[0].params, vec![json!(7)]);
+
+        let stale = AsyncClient::with_transport(Renumbered { node, id: json!(0) });
+        match stale.getblockhash(7).wait() {
+            Err(Error::Rpc(RpcError::NonceMismatch)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn retries_transient_errors() {
+        let node = MockNode::new();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+        node.handle("getblockcount", move |_| {
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(MockError::new(-28, "Loading block index...")),
+                _ => Ok(json!(5)),
+            }
+        });
+        node.fail("sendrawtransaction", -28, "Loading block index...");
+        let client = node.async_client().with_retry_policy(fast_retries());
+
+        assert_eq!(run(client.getblockcount()).unwrap(), 5);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        node.fail("getblockcount", -28, "Loading block index...");
+        node.clear_calls();
+        assert!(matches!(run(client.getblockcount()), Err(Error::InWarmup(_))));
+        assert_eq!(node.calls_to("getblockcount").len(), 3);
+
+        // Not idempotent, so never repeated.
+        assert!(matches!(run(client.sendrawtransaction("00")), Err(Error::InWarmup(_))));
+        assert_eq!(node.calls_to("sendrawtransaction").len(), 1);
+    }
+
+    fn send_batch<T: AsyncTransport>(client: &AsyncClient<T>) {
+        let mut batch = client.batch();
+        let hash = batch.call::<String>("getblockhash", vec![json!(1)]);
+        let tx = batch.getrawtransaction("aa");
+        let mut response = batch.send().wait().unwrap();
+        assert_eq!(response.take(hash).unwrap(), "00ff");
+        assert!(matches!(response.take(tx), Err(Error::NoInformation(_))));
+    }
+
+    #[test]
+    fn sends_batches() {
+        let node = MockNode::new();
+        node.respond("getblockhash", json!("00ff"));
+        node.fail("getrawtransaction", -5, "No such mempool transaction");
+
+        send_batch(&node.async_client());
+        send_batch(&AsyncClient::with_transport(Sequential(node.clone())));
+        assert_eq!(node.calls().len(), 4);
+
+        assert!(node.async_client().batch().send().wait().is_ok());
+        assert_eq!(node.calls().len(), 4);
+    }
+
+    #[test]
+    fn waits_for_operations() {
+        let node = MockNode::new();
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&polls);
+        node.handle("z_getoperationstatus", move |_| {
+            let status = match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => "queued",
+                1 => "executing",
+                _ => "success",
+            };
+            Ok(json!([{ "id": "opid-1", "status": status, "creation_time": 1 }]))
+        });
+        node.respond(
+            "z_getoperationresult",
+            json!([{
+                "id": "opid-1",
+                "status": "success",
+                "creation_time": 1,
+                "result": { "txid": "aa" },
+            }]),
+        );
+        let client = node.async_client();
+        let id = OperationId::from("opid-1".to_owned());
+        let ms = Duration::from_millis(1);
+
+        let txid = run(client.wait_for_operation(&id, ms, Duration::from_secs(10)));
+        assert_eq!(txid.unwrap(), "aa");
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+        assert_eq!(node.calls_to("z_getoperationresult"), vec![vec![json!(["opid-1"])]]);
+
+        node.respond(
+            "z_getoperationstatus",
+            json!([{ "id": "opid-1", "status": "executing", "creation_time": 1 }]),
+        );
+        match run(client.wait_for_operation(&id, ms, Duration::from_millis(0))) {
+            Err(Error::OperationTimeout(ref timed_out)) => assert_eq!(*timed_out, id),
+            other => panic!("unexpected {:?}", other),
+        }
+        let unknown = OperationId::from("opid-2".to_owned());
+        match run(client.wait_for_operation(&unknown, ms, ms)) {
+            Err(Error::NoInformation(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
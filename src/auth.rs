@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "async")]
+use super::{AsyncClient, AsyncHttpTransport};
+use super::{Client, HttpTransport, Network, Result};
+
+const COOKIE_FILE_NAME: &str = ".cookie";
+
+/// Cookie written by the node on startup, re-read when the node rejects it.
+#[derive(Debug)]
+pub(crate) struct CookieFile {
+    path: PathBuf,
+    credentials: RwLock<(String, String)>,
+}
+
+impl CookieFile {
+    fn load(path: PathBuf) -> io::Result<CookieFile> {
+        let credentials = read_cookie(&path)?;
+        Ok(CookieFile {
+            path,
+            credentials: RwLock::new(credentials),
+        })
+    }
+
+    fn credentials(&self) -> (String, String) {
+        self.credentials.read().unwrap().clone()
+    }
+
+    // Re-reads the cookie; returns whether the node rotated it since the last read.
+    fn reload(&self) -> io::Result<bool> {
+        let credentials = read_cookie(&self.path)?;
+        let mut current = self.credentials.write().unwrap();
+        if *current == credentials {
+            return Ok(false);
+        }
+        *current = credentials;
+        Ok(true)
+    }
+}
+
+/// Basic authentication of `HttpTransport` and `AsyncHttpTransport`, from fixed credentials
+/// or a cookie file.
+#[derive(Clone, Debug)]
+pub(crate) struct Credentials {
+    user: Option<String>,
+    password: Option<String>,
+    cookie: Option<Arc<CookieFile>>,
+}
+
+impl Credentials {
+    pub(crate) fn new(user: Option<String>, password: Option<String>) -> Credentials {
+        Credentials {
+            user,
+            password,
+            cookie: None,
+        }
+    }
+
+    fn from_cookie_file(path: PathBuf) -> io::Result<Credentials> {
+        let cookie = CookieFile::load(path)?;
+        let (user, password) = cookie.credentials();
+        Ok(Credentials {
+            user: Some(user),
+            password: Some(password),
+            cookie: Some(Arc::new(cookie)),
+        })
+    }
+
+    /// User given at creation; for cookie authentication, the user of the cookie read
+    /// at creation.
+    pub(crate) fn user(&self) -> &Option<String> {
+        &self.user
+    }
+
+    pub(crate) fn password(&self) -> &Option<String> {
+        &self.password
+    }
+
+    // Credentials to send, following the cookie as it rotates.
+    pub(crate) fn basic_auth(&self) -> Option<(String, Option<String>)> {
+        match self.cookie {
+            Some(ref cookie) => {
+                let (user, password) = cookie.credentials();
+                Some((user, Some(password)))
+            }
+            None => self.user.clone().map(|user| (user, self.password.clone())),
+        }
+    }
+
+    // Called when the node rejects the credentials; returns whether a rotated cookie
+    // was read and the request is worth repeating.
+    pub(crate) fn reload_cookie(&self) -> bool {
+        let cookie = match self.cookie {
+            Some(ref cookie) => cookie,
+            None => return false,
+        };
+        match cookie.reload() {
+            Ok(rotated) => {
+                if rotated {
+                    debug!("Reloaded RPC cookie from {}", cookie.path.display());
+                }
+                rotated
+            }
+            Err(e) => {
+                warn!("Unable to read RPC cookie {}: {}", cookie.path.display(), e);
+                false
+            }
+        }
+    }
+}
+
+fn read_cookie(path: &Path) -> io::Result<(String, String)> {
+    let content = fs::read_to_string(path)?;
+    let mut parts = content.trim().splitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some(user), Some(password)) if !user.is_empty() => {
+            Ok((user.to_owned(), password.to_owned()))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Malformed cookie file {}", path.display()),
+        )),
+    }
+}
+
+impl Client {
+    /// Creates a client authenticating with the node's `.cookie` file.
+    ///
+    /// The cookie is read again when the node restarts and rotates it.
+    pub fn from_cookie_file<S, P>(url: S, path: P) -> Result<Client>
+    where
+        S: Into<String>,
+        P: Into<PathBuf>,
+    {
+        let credentials = Credentials::from_cookie_file(path.into())?;
+        Ok(Client::with_transport(HttpTransport::with_credentials(url, credentials)))
+    }
+
+    /// Creates a client from a `zcash.conf` or `bitcoin.conf` file.
+    ///
+    /// `network` selects the coin and the default chain; `testnet=1` or `regtest=1`
+    /// in the file switch to the corresponding chain of the same coin. Without
+    /// `rpcuser` and `rpcpassword` the client falls back to cookie authentication.
+    pub fn from_config_file<P: AsRef<Path>>(path: P, network: Network) -> Result<Client> {
+        let (url, credentials, network) = read_config(path.as_ref(), network)?;
+        let transport = HttpTransport::with_credentials(url, credentials);
+        Ok(Client::with_transport(transport).with_network(network))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncClient {
+    /// Creates a client authenticating with the node's `.cookie` file, like
+    /// `Client::from_cookie_file`.
+    pub fn from_cookie_file<S, P>(url: S, path: P) -> Result<AsyncClient>
+    where
+        S: Into<String>,
+        P: Into<PathBuf>,
+    {
+        let credentials = Credentials::from_cookie_file(path.into())?;
+        let transport = AsyncHttpTransport::with_credentials(url, credentials);
+        Ok(AsyncClient::with_transport(transport))
+    }
+
+    /// Creates a client from a `zcash.conf` or `bitcoin.conf` file, like
+    /// `Client::from_config_file`.
+    pub fn from_config_file<P: AsRef<Path>>(path: P, network: Network) -> Result<AsyncClient> {
+        let (url, credentials, network) = read_config(path.as_ref(), network)?;
+        let transport = AsyncHttpTransport::with_credentials(url, credentials);
+        Ok(AsyncClient::with_transport(transport).with_network(network))
+    }
+}
+
+// Reads the node URL, credentials and chain from a config file.
+fn read_config(path: &Path, network: Network) -> Result<(String, Credentials, Network)> {
+    let config = Config::parse(&fs::read_to_string(path)?)?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let node = config.node(&dir, network)?;
+    let credentials = match node.auth {
+        ConfigAuth::Password(user, password) => Credentials::new(Some(user), Some(password)),
+        ConfigAuth::Cookie(cookie) => Credentials::from_cookie_file(cookie)?,
+    };
+    Ok((node.url, credentials, node.network))
+}
+
+// Node settings of a config file.
+#[derive(Debug, PartialEq)]
+struct NodeConfig {
+    url: String,
+    network: Network,
+    auth: ConfigAuth,
+}
+
+#[derive(Debug, PartialEq)]
+enum ConfigAuth {
+    Password(String, String),
+    Cookie(PathBuf),
+}
+
+// Settings of a `.conf` file, with bitcoind style `[section]` overrides.
+struct Config {
+    values: HashMap<(Option<String>, String), String>,
+}
+
+impl Config {
+    fn parse(content: &str) -> io::Result<Config> {
+        let mut values = HashMap::new();
+        let mut section = None;
+        for line in content.lines() {
+            let line = match line.find('#') {
+                Some(pos) => &line[..pos],
+                None => line,
+            }.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = Some(line[1..line.len() - 1].trim().to_owned());
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid config line: {}", line),
+                )
+            })?;
+            values
+                .entry((section.clone(), key.to_owned()))
+                .or_insert_with(|| value.trim().to_owned());
+        }
+        Ok(Config { values })
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.values
+            .get(&(Some(section.to_owned()), key.to_owned()))
+            .or_else(|| self.values.get(&(None, key.to_owned())))
+            .map(String::as_str)
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.values
+            .get(&(None, key.to_owned()))
+            .is_some_and(|value| value != "0")
+    }
+
+    // Resolves the settings of the selected chain; `dir` is the directory of the file,
+    // the default data directory.
+    fn node(&self, dir: &Path, network: Network) -> io::Result<NodeConfig> {
+        let network = if self.flag("regtest") {
+            network.regtest()
+        } else if self.flag("testnet") {
+            network.testnet()
+        } else {
+            network
+        };
+        let section = network.config_section();
+
+        let bind = self
+            .get(section, "rpcconnect")
+            .or_else(|| self.get(section, "rpcbind"))
+            .unwrap_or("127.0.0.1");
+        let (host, bind_port) = split_host_port(bind);
+        let port = match self.get(section, "rpcport") {
+            Some(port) => port.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid rpcport {}", port),
+                )
+            })?,
+            None => bind_port.unwrap_or_else(|| network.rpc_port()),
+        };
+        let url = format!("http://{}:{}", host, port);
+
+        let user = self.get(section, "rpcuser");
+        let password = self.get(section, "rpcpassword");
+        if let (Some(user), Some(password)) = (user, password) {
+            let auth = ConfigAuth::Password(user.to_owned(), password.to_owned());
+            return Ok(NodeConfig { url, network, auth });
+        }
+
+        let mut datadir = match self.get(section, "datadir") {
+            Some(datadir) => PathBuf::from(datadir),
+            None => dir.to_path_buf(),
+        };
+        if let Some(subdir) = network.datadir_subdir() {
+            datadir.push(subdir);
+        }
+        let cookie = match self.get(section, "rpccookiefile") {
+            Some(cookie) => datadir.join(cookie),
+            None => datadir.join(COOKIE_FILE_NAME),
+        };
+        let auth = ConfigAuth::Cookie(cookie);
+        Ok(NodeConfig { url, network, auth })
+    }
+}
+
+// Splits `host`, `host:port`, `[v6]` or `[v6]:port`; wildcard binds are reached via localhost.
+fn split_host_port(bind: &str) -> (String, Option<u16>) {
+    let (host, port) = if bind.starts_with('[') {
+        match bind.find(']') {
+            Some(end) => (&bind[..=end], bind[end + 1..].trim_start_matches(':')),
+            None => (bind, ""),
+        }
+    } else if bind.matches(':').count() == 1 {
+        let pos = bind.find(':').unwrap();
+        (&bind[..pos], &bind[pos + 1..])
+    } else {
+        (bind, "")
+    };
+
+    let host = match host {
+        "0.0.0.0" | "::" | "[::]" => "127.0.0.1".to_owned(),
+        host if host.contains(':') && !host.starts_with('[') => format!("[{}]", host),
+        host => host.to_owned(),
+    };
+    (host, port.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{self, Value};
+
+    use super::{split_host_port, Config, ConfigAuth, NodeConfig};
+    use mock::MockServer;
+    use {Client, Error, Network};
+
+    fn node(content: &str, network: Network) -> NodeConfig {
+        Config::parse(content)
+            .unwrap()
+            .node(Path::new("/home/user/.zcash"), network)
+            .unwrap()
+    }
+
+    fn password(user: &str, password: &str) -> ConfigAuth {
+        ConfigAuth::Password(user.to_owned(), password.to_owned())
+    }
+
+    fn cookie(path: &str) -> ConfigAuth {
+        ConfigAuth::Cookie(PathBuf::from(path))
+    }
+
+    #[test]
+    fn reads_chain_settings() {
+        let mainnet = node("rpcuser=alice\nrpcpassword=secret\n", Network::ZcashMainnet);
+        assert_eq!(mainnet.url, "http://127.0.0.1:8232");
+        assert_eq!(mainnet.network, Network::ZcashMainnet);
+        assert_eq!(mainnet.auth, password("alice", "secret"));
+
+        let content = "
+            # Shared settings
+            testnet=1
+            rpcuser=alice
+            rpcpassword=secret # trailing comment
+            [main]
+            rpcport=1000
+            [test]
+            rpcpassword=testnet-secret
+        ";
+        let testnet = node(content, Network::ZcashMainnet);
+        assert_eq!(testnet.url, "http://127.0.0.1:18232");
+        assert_eq!(testnet.network, Network::ZcashTestnet);
+        assert_eq!(testnet.auth, password("alice", "testnet-secret"));
+
+        let content = "regtest=1\ntestnet=1\n[regtest]\nrpcport=2000\n";
+        let regtest = node(content, Network::BitcoinMainnet);
+        assert_eq!(regtest.url, "http://127.0.0.1:2000");
+        assert_eq!(regtest.network, Network::BitcoinRegtest);
+        assert_eq!(regtest.auth, cookie("/home/user/.zcash/regtest/.cookie"));
+
+        let disabled = node("testnet=0\nregtest=0\n", Network::ZcashMainnet);
+        assert_eq!(disabled.network, Network::ZcashMainnet);
+        assert_eq!(disabled.auth, cookie("/home/user/.zcash/.cookie"));
+    }
+
+    #[test]
+    fn first_value_wins() {
+        let content = "rpcuser=alice\nrpcuser=bob\nrpcpassword=first\nrpcpassword=second\n";
+        assert_eq!(node(content, Network::ZcashMainnet).auth, password("alice", "first"));
+    }
+
+    #[test]
+    fn reads_rpc_address() {
+        let url = |content: &str| node(content, Network::ZcashMainnet).url;
+        assert_eq!(url("rpcbind=10.0.0.1"), "http://10.0.0.1:8232");
+        assert_eq!(url("rpcbind=10.0.0.1:9000"), "http://10.0.0.1:9000");
+        assert_eq!(url("rpcbind=0.0.0.0\nrpcport=9001"), "http://127.0.0.1:9001");
+        assert_eq!(url("rpcbind=10.0.0.1\nrpcconnect=node.local"), "http://node.local:8232");
+        assert_eq!(url("rpcconnect=node.local:9000\nrpcport=9001"), "http://node.local:9001");
+        assert_eq!(url("rpcbind=[::1]:9000"), "http://[::1]:9000");
+
+        let error = Config::parse("rpcport=port")
+            .unwrap()
+            .node(Path::new(""), Network::ZcashMainnet)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Invalid rpcport port");
+        assert!(Config::parse("rpcport").is_err());
+    }
+
+    #[test]
+    fn reads_cookie_path() {
+        let content = "testnet=1\ndatadir=/var/lib/zcashd\n";
+        let testnet = node(content, Network::ZcashMainnet);
+        assert_eq!(testnet.auth, cookie("/var/lib/zcashd/testnet3/.cookie"));
+
+        let content = "datadir=/var/lib/zcashd\nrpccookiefile=rpc.cookie\n";
+        let relative = node(content, Network::ZcashMainnet);
+        assert_eq!(relative.auth, cookie("/var/lib/zcashd/rpc.cookie"));
+
+        let content = "rpccookiefile=/run/zcashd/rpc.cookie\nrpcuser=alice\n";
+        let absolute = node(content, Network::ZcashMainnet);
+        assert_eq!(absolute.auth, cookie("/run/zcashd/rpc.cookie"));
+    }
+
+    #[test]
+    fn splits_host_and_port() {
+        let cases = [
+            ("localhost", "localhost", None),
+            ("localhost:8232", "localhost", Some(8232)),
+            ("0.0.0.0:8232", "127.0.0.1", Some(8232)),
+            ("::", "127.0.0.1", None),
+            ("[::]:8232", "127.0.0.1", Some(8232)),
+            ("::1", "[::1]", None),
+            ("[::1]", "[::1]", None),
+            ("[fe80::1]:8232", "[fe80::1]", Some(8232)),
+            ("host:port", "host", None),
+        ];
+        for &(bind, host, port) in &cases {
+            assert_eq!(split_host_port(bind), (host.to_owned(), port), "{}", bind);
+        }
+    }
+
+    // Base64 of `__cookie__:first` and `__cookie__:second`.
+    const FIRST: &str = "Basic X19jb29raWVfXzpmaXJzdA==";
+    const SECOND: &str = "Basic X19jb29raWVfXzpzZWNvbmQ=";
+
+    #[test]
+    fn reloads_rotated_cookie() {
+        let datadir = env::temp_dir().join(format!("zcash-rpc-cookie-{}", process::id()));
+        fs::create_dir_all(datadir.join("regtest")).unwrap();
+        let cookie = datadir.join("regtest").join(".cookie");
+        fs::write(&cookie, "__cookie__:first").unwrap();
+
+        // Accepts only `valid` and records the credentials of each request.
+        let valid = Arc::new(Mutex::new(FIRST));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let server = {
+            let valid = Arc::clone(&valid);
+            let seen = Arc::clone(&seen);
+            MockServer::start(move |request| {
+                let auth = request.header("Authorization").unwrap_or("").to_owned();
+                seen.lock().unwrap().push(auth.clone());
+                if auth != *valid.lock().unwrap() {
+                    return Ok((401, Vec::new()));
+                }
+                let request: Value = serde_json::from_slice(&request.body)?;
+                let response = json!({ "result": 7, "error": null, "id": request["id"] });
+                Ok((200, serde_json::to_vec(&response)?))
+            }).unwrap()
+        };
+        let config = datadir.join("zcash.conf");
+        let content = format!("regtest=1\n[regtest]\nrpcport={}\n", server.addr().port());
+        fs::write(&config, content).unwrap();
+        let client = Client::from_config_file(&config, Network::ZcashMainnet).unwrap();
+
+        assert_eq!(client.getblockcount().unwrap(), 7);
+        // The node restarts with a new cookie.
+        fs::write(&cookie, "__cookie__:second\n").unwrap();
+        *valid.lock().unwrap() = SECOND;
+        assert_eq!(client.getblockcount().unwrap(), 7);
+        assert_eq!(client.getblockcount().unwrap(), 7);
+        assert_eq!(*seen.lock().unwrap(), vec![FIRST, FIRST, SECOND, SECOND]);
+
+        // An unchanged cookie is not worth a second attempt.
+        *valid.lock().unwrap() = "";
+        seen.lock().unwrap().clear();
+        assert!(matches!(client.getblockcount(), Err(Error::Unauthorized(401))));
+        assert_eq!(*seen.lock().unwrap(), vec![SECOND]);
+
+        fs::remove_dir_all(&datadir).unwrap();
+    }
+}
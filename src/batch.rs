@@ -0,0 +1,258 @@
+use std::marker::PhantomData;
+
+use exonum_jsonrpc::{Request, Response};
+use serde::Deserialize;
+
+use redact::Redactor;
+use super::{trace_request, Client, Error, Params, RawTransactionInfo, Result, RpcError, Transaction,
+            Transport, ZecRawTransactionInfo};
+
+/// Queue of JSON-RPC calls sent to the node in a single HTTP round trip.
+///
+/// Created by `Client::batch` or `AsyncClient::batch`.
+#[derive(Debug)]
+pub struct Batch<'a, C: 'a = Client> {
+    pub(crate) client: &'a C,
+    pub(crate) requests: Vec<Request>,
+}
+
+pub(crate) mod private {
+    pub trait Sealed {}
+}
+
+/// Client whose calls can be queued in a `Batch`: `Client`, or `AsyncClient` with the
+/// `async` feature. Sealed, so it cannot be implemented outside this crate.
+pub trait BatchClient: private::Sealed {
+    #[doc(hidden)]
+    fn build_request(&self, method: &str, params: Params) -> Request;
+}
+
+impl<T: Transport> private::Sealed for Client<T> {}
+
+impl<T: Transport> BatchClient for Client<T> {
+    fn build_request(&self, method: &str, params: Params) -> Request {
+        Client::build_request(self, method, params)
+    }
+}
+
+/// Handle to a call queued in a `Batch`, used to take its typed result from a `BatchResponse`.
+#[derive(Debug)]
+pub struct BatchCall<T> {
+    index: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Responses of a sent `Batch`, matched to their calls by request id.
+#[derive(Debug, Default)]
+pub struct BatchResponse {
+    responses: Vec<Option<Response>>,
+}
+
+impl<'a, C: BatchClient> Batch<'a, C> {
+    pub(crate) fn new(client: &'a C) -> Batch<'a, C> {
+        Batch {
+            client,
+            requests: Vec::new(),
+        }
+    }
+
+    pub fn call<R>(&mut self, method: &str, params: Params) -> BatchCall<R>
+    where
+        for<'de> R: Deserialize<'de>,
+    {
+        let request = self.client.build_request(method, params);
+        self.requests.push(request);
+        BatchCall {
+            index: self.requests.len() - 1,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    pub fn getrawtransaction(&mut self, txid: &str) -> BatchCall<String> {
+        let params = json!([txid, 0]).as_array().cloned().unwrap();
+        self.call("getrawtransaction", params)
+    }
+
+    pub fn getrawtransaction_tx(&mut self, txid: &str) -> BatchCall<Transaction> {
+        let params = json!([txid, 0]).as_array().cloned().unwrap();
+        self.call("getrawtransaction", params)
+    }
+
+    pub fn getrawtransaction_verbose(&mut self, txid: &str) -> BatchCall<RawTransactionInfo> {
+        let params = json!([txid, 1]).as_array().cloned().unwrap();
+        self.call("getrawtransaction", params)
+    }
+
+    pub fn getrawtransaction_verbose_zec(
+        &mut self,
+        txid: &str,
+    ) -> BatchCall<ZecRawTransactionInfo> {
+        let params = json!([txid, 1]).as_array().cloned().unwrap();
+        self.call("getrawtransaction", params)
+    }
+
+    pub fn getblockhash(&mut self, height: u64) -> BatchCall<String> {
+        let params = json!([height]).as_array().cloned().unwrap();
+        self.call("getblockhash", params)
+    }
+}
+
+impl<'a, T: Transport> Batch<'a, Client<T>> {
+    pub fn send(self) -> Result<BatchResponse> {
+        if self.requests.is_empty() {
+            return Ok(BatchResponse::default());
+        }
+
+        let received = self.client.transport().send_batch(&self.requests)?;
+        Ok(BatchResponse::collect(
+            &self.requests,
+            received,
+            &self.client.redactor,
+        ))
+    }
+}
+
+impl BatchResponse {
+    // Orders `received` like `requests`, logging each matched pair.
+    pub(crate) fn collect(
+        requests: &[Request],
+        received: Vec<Response>,
+        redactor: &Redactor,
+    ) -> BatchResponse {
+        let mut responses: Vec<Option<Response>> = vec![None; requests.len()];
+        for response in received {
+            if let Some(index) = requests.iter().position(|r| r.id == response.id) {
+                trace_request(redactor, &requests[index], &response);
+                responses[index] = Some(response);
+            }
+        }
+        BatchResponse { responses }
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// Takes the result of the given call.
+    ///
+    /// Fails with `RpcError::NonceMismatch` if the node did not answer this call.
+    pub fn take<T>(&mut self, call: BatchCall<T>) -> Result<T>
+    where
+        for<'de> T: Deserialize<'de>,
+    {
+        self.responses
+            .get_mut(call.index)
+            .and_then(Option::take)
+            .ok_or(Error::Rpc(RpcError::NonceMismatch))?
+            .into_result::<T>()
+            .map_err(Error::from)
+    }
+}
+
+impl<T: Transport> Client<T> {
+    pub fn batch(&self) -> Batch<'_, Client<T>> {
+        Batch::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum_jsonrpc::{Request, Response};
+    use serde_json::Value;
+
+    use mock::MockNode;
+    use {Client, Error, Result, RpcError, Transport};
+
+    // Answers batches in reverse order, leaving out the response with id `dropped`.
+    struct Shuffled {
+        node: MockNode,
+        dropped: Option<Value>,
+    }
+
+    impl Transport for Shuffled {
+        fn send_request(&self, request: &Request) -> Result<Response> {
+            self.node.send_request(request)
+        }
+
+        fn send_batch(&self, requests: &[Request]) -> Result<Vec<Response>> {
+            let mut responses = self.node.send_batch(requests)?;
+            responses.reverse();
+            responses.retain(|response| Some(&response.id) != self.dropped.as_ref());
+            responses.push(Response {
+                result: Some(json!("unrequested")),
+                error: None,
+                id: json!(1000),
+            });
+            Ok(responses)
+        }
+    }
+
+    fn client(dropped: Option<Value>) -> (MockNode, Client<Shuffled>) {
+        let node = MockNode::new();
+        node.handle("getblockhash", |params| Ok(json!(format!("hash{}", params[0]))));
+        node.respond("getblockcount", json!(7));
+        node.fail("getrawtransaction", -5, "No such mempool transaction");
+        let transport = Shuffled {
+            node: node.clone(),
+            dropped,
+        };
+        (node, Client::with_transport(transport))
+    }
+
+    #[test]
+    fn matches_responses_by_id() {
+        let (node, client) = client(None);
+        let mut batch = client.batch();
+        let hashes: Vec<_> = (0..5).map(|height| batch.getblockhash(height)).collect();
+        let count = batch.call::<u64>("getblockcount", vec![]);
+        assert_eq!(batch.len(), 6);
+
+        let mut responses = batch.send().unwrap();
+        assert_eq!(responses.len(), 6);
+        assert_eq!(responses.take(count).unwrap(), 7);
+        for (height, hash) in hashes.into_iter().enumerate() {
+            assert_eq!(responses.take(hash).unwrap(), format!("hash{}", height));
+        }
+        assert_eq!(node.calls().len(), 6);
+    }
+
+    #[test]
+    fn missing_responses_and_errors_fail_their_calls_only() {
+        // Ids are assigned from 1 in the order calls are queued.
+        let (_, client) = client(Some(json!(2)));
+        let mut batch = client.batch();
+        let first = batch.getblockhash(1);
+        let missing = batch.getblockhash(2);
+        let failed = batch.getrawtransaction("00");
+        let count = batch.call::<u64>("getblockcount", vec![]);
+
+        let mut responses = batch.send().unwrap();
+        assert!(matches!(responses.take(missing), Err(Error::Rpc(RpcError::NonceMismatch))));
+        let error = responses.take(failed).unwrap_err();
+        assert!(matches!(error, Error::NoInformation(_)));
+        assert_eq!(error.message(), Some("No such mempool transaction"));
+        assert_eq!(responses.take(first).unwrap(), "hash1");
+        assert_eq!(responses.take(count).unwrap(), 7);
+    }
+
+    #[test]
+    fn empty_batches_are_not_sent() {
+        let (node, client) = client(None);
+        let batch = client.batch();
+        assert!(batch.is_empty());
+        assert!(batch.send().unwrap().is_empty());
+        assert!(node.calls().is_empty());
+    }
+}
@@ -0,0 +1,361 @@
+//! Base58check, bech32 and compact size encodings used by addresses and transactions.
+
+use sha2::{Digest, Sha256};
+
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    let first = Sha256::digest(data);
+    let second = Sha256::digest(first);
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&second);
+    hash
+}
+
+pub fn base58check_encode(payload: &[u8]) -> String {
+    let checksum = sha256d(payload);
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&checksum[..4]);
+    bs58::encode(data).into_string()
+}
+
+pub fn base58check_decode(s: &str) -> Option<Vec<u8>> {
+    let mut data = bs58::decode(s).into_vec().ok()?;
+    if data.len() < 4 {
+        return None;
+    }
+    let checksum = data.split_off(data.len() - 4);
+    if sha256d(&data)[..4] != checksum[..] {
+        return None;
+    }
+    Some(data)
+}
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// Checksum flavour of a bech32 string (BIP 173 or BIP 350).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bech32Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Bech32Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Bech32Variant::Bech32 => 1,
+            Bech32Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for &value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 0x1f));
+    values
+}
+
+/// Encodes 5-bit groups. Unlike BIP 173 no length limit is enforced, as unified
+/// addresses are longer than 90 characters.
+pub fn bech32_encode(hrp: &str, data: &[u8], variant: Bech32Variant) -> String {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; 6]);
+    let checksum = polymod(&values) ^ variant.constant();
+
+    let mut encoded = String::with_capacity(hrp.len() + 1 + data.len() + 6);
+    encoded.push_str(hrp);
+    encoded.push('1');
+    for &value in data {
+        encoded.push(CHARSET[value as usize] as char);
+    }
+    for i in 0..6 {
+        let value = (checksum >> (5 * (5 - i))) & 0x1f;
+        encoded.push(CHARSET[value as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes a bech32 or bech32m string into its lowercase hrp and 5-bit groups.
+pub fn bech32_decode(s: &str) -> Option<(String, Vec<u8>, Bech32Variant)> {
+    let has_lower = s.bytes().any(|b| b.is_ascii_lowercase());
+    let has_upper = s.bytes().any(|b| b.is_ascii_uppercase());
+    if (has_lower && has_upper) || !s.is_ascii() {
+        return None;
+    }
+    let s = s.to_ascii_lowercase();
+    let separator = s.rfind('1')?;
+    if separator == 0 || separator + 7 > s.len() {
+        return None;
+    }
+    let (hrp, data) = (&s[..separator], &s[separator + 1..]);
+    if hrp.bytes().any(|b| !(33..=126).contains(&b)) {
+        return None;
+    }
+
+    let data = data
+        .bytes()
+        .map(|b| CHARSET.iter().position(|&c| c == b).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(&data);
+    let variant = match polymod(&values) {
+        c if c == Bech32Variant::Bech32.constant() => Bech32Variant::Bech32,
+        c if c == Bech32Variant::Bech32m.constant() => Bech32Variant::Bech32m,
+        _ => return None,
+    };
+    Some((hrp.to_owned(), data[..data.len() - 6].to_vec(), variant))
+}
+
+/// Regroups bits, e.g. bytes into the 5-bit groups of bech32 and back.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        let value = u32::from(value);
+        if value >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | value;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+pub fn write_compact_size(out: &mut Vec<u8>, value: u64) {
+    if value < 0xfd {
+        out.push(value as u8);
+    } else if value <= 0xffff {
+        out.push(0xfd);
+        out.extend_from_slice(&(value as u16).to_le_bytes());
+    } else if value <= 0xffff_ffff {
+        out.push(0xfe);
+        out.extend_from_slice(&(value as u32).to_le_bytes());
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Reads a compact size from the front of `cursor`, rejecting non-canonical encodings.
+pub fn read_compact_size(cursor: &mut &[u8]) -> Option<u64> {
+    let (&first, rest) = cursor.split_first()?;
+    let (len, min) = match first {
+        0xfd => (2, 0xfd),
+        0xfe => (4, 0x1_0000),
+        0xff => (8, 0x1_0000_0000),
+        value => {
+            *cursor = rest;
+            return Some(u64::from(value));
+        }
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let mut bytes = [0; 8];
+    bytes[..len].copy_from_slice(&rest[..len]);
+    let value = u64::from_le_bytes(bytes);
+    if value < min {
+        return None;
+    }
+    *cursor = &rest[len..];
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        base58check_decode, base58check_encode, bech32_decode, bech32_encode, read_compact_size,
+        write_compact_size, Bech32Variant,
+    };
+
+    // BIP 173 and BIP 350 test vectors. The 84 character strings exceeding the overall
+    // length limit are left out, since unified addresses need longer strings.
+    const VALID_BECH32: &[&str] = &[
+        "A12UEL5L",
+        "a12uel5l",
+        "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharacters\
+        bio1tt5tgs",
+        "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+        "11qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq\
+        qqqqc8247j",
+        "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+        "?1ezyfcl",
+    ];
+
+    const VALID_BECH32M: &[&str] = &[
+        "A1LQFN3A",
+        "a1lqfn3a",
+        "an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumb\
+        er11sg7hg6",
+        "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+        "11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllll\
+        llllludsr8",
+        "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+        "?1v759aa",
+    ];
+
+    const INVALID: &[&str] = &[
+        "\u{20}1nwldj5",
+        "\u{7f}1axkwrx",
+        "\u{80}1eym55h",
+        "pzry9x0s0muk",
+        "1pzry9x0s0muk",
+        "x1b4n0q5v",
+        "li1dgmt3",
+        "de1lg7wt\u{ff}",
+        "A1G7SGD8",
+        "10a06t8",
+        "1qzzfhee",
+        "\u{20}1xj0phk",
+        "\u{7f}1g6xzxy",
+        "\u{80}1vctc34",
+        "qyrz8wqd2c9m",
+        "1qyrz8wqd2c9m",
+        "y1b0jsk6g",
+        "lt1igcx5c0",
+        "in1muywd",
+        "mm1crxm3i",
+        "au1s5cgom",
+        "M1VUXWEZ",
+        "16plkw9",
+        "1p2gdwpf",
+    ];
+
+    fn check_valid(s: &str, variant: Bech32Variant) {
+        let (hrp, data, decoded_variant) =
+            bech32_decode(s).unwrap_or_else(|| panic!("{} failed to decode", s));
+        assert_eq!(decoded_variant, variant, "{}", s);
+        assert_eq!(bech32_encode(&hrp, &data, variant), s.to_ascii_lowercase());
+
+        // Flipping any data character breaks the checksum.
+        let separator = s.rfind('1').unwrap();
+        for i in separator + 1..s.len() {
+            let mut corrupted = s.to_ascii_lowercase().into_bytes();
+            corrupted[i] = if corrupted[i] == b'q' { b'p' } else { b'q' };
+            let corrupted = String::from_utf8(corrupted).unwrap();
+            assert_eq!(bech32_decode(&corrupted), None, "{}", corrupted);
+        }
+    }
+
+    #[test]
+    fn bech32_valid_vectors() {
+        for s in VALID_BECH32 {
+            check_valid(s, Bech32Variant::Bech32);
+        }
+    }
+
+    #[test]
+    fn bech32m_valid_vectors() {
+        for s in VALID_BECH32M {
+            check_valid(s, Bech32Variant::Bech32m);
+        }
+    }
+
+    #[test]
+    fn bech32_invalid_vectors() {
+        for s in INVALID {
+            assert_eq!(bech32_decode(s), None, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn bech32_checksum_depends_on_variant() {
+        let data = [0, 14, 20, 15, 7, 13, 26, 0, 25, 18, 6, 11, 13, 8, 21, 4, 20, 3, 17, 2];
+        let bech32 = bech32_encode("bc", &data, Bech32Variant::Bech32);
+        let bech32m = bech32_encode("bc", &data, Bech32Variant::Bech32m);
+        assert_ne!(bech32, bech32m);
+        assert_eq!(bech32_decode(&bech32).unwrap().2, Bech32Variant::Bech32);
+        assert_eq!(bech32_decode(&bech32m).unwrap().2, Bech32Variant::Bech32m);
+    }
+
+    #[test]
+    fn base58check_vectors() {
+        let genesis = hex::decode("0062e907b15cbf27d5425399ebf6f0fb50ebb88f18").unwrap();
+        assert_eq!(base58check_encode(&genesis), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+        assert_eq!(base58check_decode("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"), Some(genesis));
+
+        // Leading zero bytes map to leading '1's.
+        assert_eq!(base58check_encode(&[0; 21]), "1111111111111111111114oLvT2");
+        assert_eq!(base58check_decode("1111111111111111111114oLvT2"), Some(vec![0; 21]));
+        assert_eq!(base58check_decode(&base58check_encode(&[])), Some(vec![]));
+    }
+
+    #[test]
+    fn base58check_rejects_corruption() {
+        // Wrong checksum, invalid characters and too short payloads.
+        assert_eq!(base58check_decode("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"), None);
+        assert_eq!(base58check_decode("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN0"), None);
+        assert_eq!(base58check_decode("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNI"), None);
+        assert_eq!(base58check_decode(""), None);
+        assert_eq!(base58check_decode("1"), None);
+    }
+
+    #[test]
+    fn compact_size_round_trip() {
+        let cases: &[(u64, &[u8])] = &[
+            (0, &[0x00]),
+            (0xfc, &[0xfc]),
+            (0xfd, &[0xfd, 0xfd, 0x00]),
+            (0xffff, &[0xfd, 0xff, 0xff]),
+            (0x1_0000, &[0xfe, 0x00, 0x00, 0x01, 0x00]),
+            (0x1_0000_0000, &[0xff, 0, 0, 0, 0, 1, 0, 0, 0]),
+        ];
+        for &(value, encoded) in cases {
+            let mut out = Vec::new();
+            write_compact_size(&mut out, value);
+            assert_eq!(out, encoded);
+            let mut cursor = encoded;
+            assert_eq!(read_compact_size(&mut cursor), Some(value));
+            assert!(cursor.is_empty());
+        }
+    }
+
+    #[test]
+    fn compact_size_rejects_non_canonical() {
+        let cases: &[&[u8]] = &[
+            &[],
+            &[0xfd, 0xfc, 0x00],
+            &[0xfe, 0xff, 0xff, 0x00, 0x00],
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0],
+            &[0xfd, 0x00],
+        ];
+        for &encoded in cases {
+            let mut cursor = encoded;
+            assert_eq!(read_compact_size(&mut cursor), None, "{:?}", encoded);
+        }
+    }
+}
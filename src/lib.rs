@@ -1,5 +1,4 @@
 extern crate exonum_jsonrpc;
-#[macro_use]
 extern crate serde_derive;
 extern crate serde;
 #[macro_use]
@@ -8,43 +7,167 @@ extern crate serde_json;
 extern crate log;
 extern crate failure;
 #[macro_use]
-extern crate failure_derive;
-#[macro_use]
 extern crate display_derive;
+extern crate blake2b_simd;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(all(test, feature = "async"))]
+extern crate tokio;
+#[cfg(feature = "async")]
+extern crate tokio_timer;
+extern crate bs58;
+extern crate hex;
+extern crate reqwest;
+extern crate sha2;
+extern crate zeroize;
+
+mod address;
+mod amount;
+#[macro_use]
+mod api;
+#[cfg(feature = "async")]
+mod async_client;
+mod auth;
+mod batch;
+mod encoding;
+mod method;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+mod network;
+mod params;
+mod redact;
+mod retry;
+mod secret;
+mod shielded;
+mod transaction;
+mod transport;
+mod wallet;
 
 use std::collections::BTreeMap;
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
+use failure::Fail;
 use serde::{Deserialize, Serialize};
 use serde_json::value::Value;
 
-use exonum_jsonrpc::client::Client as RpcClient;
 pub use exonum_jsonrpc::error::Error as RpcError;
-
-#[derive(Fail, Debug, Display)]
+pub use exonum_jsonrpc::{Request, Response};
+
+pub use address::{Address, AddressError, Payload};
+pub use amount::{Amount, ParseAmountError, COIN};
+#[cfg(feature = "async")]
+pub use async_client::{AsyncClient, AsyncHttpTransport, AsyncTransport, RpcFuture};
+pub use batch::{Batch, BatchCall, BatchClient, BatchResponse};
+pub use method::RpcMethod;
+pub use network::{Network, NetworkUpgrade, COINBASE_MATURITY, ZCASH_DEFAULT_FEE};
+pub use redact::Redaction;
+pub use retry::RetryPolicy;
+pub use secret::SecretString;
+pub use shielded::{Receiver, SaplingAddress, ShieldedAddress, SproutAddress, UnifiedAddress};
+pub use transaction::{ActionDescription, JoinSplitData, JoinSplitDescription, OrchardShieldedData,
+                      OutPoint, OutputDescription, SaplingShieldedData, SpendDescription,
+                      Transaction, TransactionError, TransparentInput, TransparentOutput};
+pub use transport::{HttpTransport, Transport};
+pub use wallet::UnlockedWallet;
+
+/// Error of a call; RPC errors reported by the node keep their message, and their
+/// code is available through `Error::code`.
+#[derive(Debug, Display)]
 pub enum Error {
+    #[display(fmt = "Method not found. {}", _0)]
+    MethodNotFound(String),
+    #[display(fmt = "Invalid request. {}", _0)]
+    InvalidRequest(String),
+    #[display(fmt = "Invalid params. {}", _0)]
+    InvalidParams(String),
+    #[display(fmt = "Internal error. {}", _0)]
+    InternalError(String),
+    #[display(fmt = "Parse error. {}", _0)]
+    ParseError(String),
+    #[display(fmt = "Misc error. {}", _0)]
+    Misc(String),
+    #[display(fmt = "Forbidden by safe mode. {}", _0)]
+    ForbiddenBySafeMode(String),
+    #[display(fmt = "Invalid amount. {}", _0)]
+    InvalidAmount(String),
+    #[display(fmt = "Wallet error: {}", _0)]
+    WalletError(String),
     #[display(fmt = "No information. {}", _0)]
     NoInformation(String),
+    #[display(fmt = "Insufficient funds. {}", _0)]
+    InsufficientFunds(String),
     #[display(fmt = "Memory pool error. {}", _0)]
     Memory(String),
+    #[display(fmt = "Invalid parameter. {}", _0)]
+    InvalidParameter(String),
+    #[display(fmt = "Node is not connected to the network. {}", _0)]
+    ClientNotConnected(String),
+    #[display(fmt = "Node is in initial block download. {}", _0)]
+    InInitialDownload(String),
+    #[display(fmt = "Invalid account name. {}", _0)]
+    InvalidAccountName(String),
+    #[display(fmt = "Keypool ran out. {}", _0)]
+    KeypoolRanOut(String),
+    #[display(fmt = "Wallet is locked. {}", _0)]
+    WalletUnlockNeeded(String),
+    #[display(fmt = "Wallet passphrase is incorrect. {}", _0)]
+    WalletPassphraseIncorrect(String),
+    #[display(fmt = "Wallet is in the wrong encryption state. {}", _0)]
+    WalletWrongEncState(String),
+    #[display(fmt = "Wallet encryption failed. {}", _0)]
+    WalletEncryptionFailed(String),
+    #[display(fmt = "Wallet is already unlocked. {}", _0)]
+    WalletAlreadyUnlocked(String),
+    #[display(fmt = "Wallet not found. {}", _0)]
+    WalletNotFound(String),
+    #[display(fmt = "Wallet not specified. {}", _0)]
+    WalletNotSpecified(String),
+    #[display(fmt = "Database error. {}", _0)]
+    Database(String),
+    #[display(fmt = "Deserialization error. {}", _0)]
+    Deserialization(String),
+    #[display(fmt = "Node already added. {}", _0)]
+    NodeAlreadyAdded(String),
+    #[display(fmt = "Node has not been added. {}", _0)]
+    NodeNotAdded(String),
     #[display(fmt = "Transaction is incorrect. {}", _0)]
     TransactionIncorrect(String),
     #[display(fmt = "Transaction rejected. {}", _0)]
     TransactionRejected(String),
-    #[display(fmt = "Insufficient funds.")]
-    InsufficientFunds,
-    #[display(fmt = "Wallet error: {}", _0)]
-    WalletError(String),
-    #[display(fmt = "Invalid amount")]
-    InvalidAmount,
-    #[display(fmt = "Transaction already in chain.")]
-    TransactionAlreadyInChain,
+    #[display(fmt = "Transaction already in chain. {}", _0)]
+    TransactionAlreadyInChain(String),
+    #[display(fmt = "Node is warming up. {}", _0)]
+    InWarmup(String),
+    #[display(fmt = "Peer is not connected. {}", _0)]
+    NodeNotConnected(String),
+    #[display(fmt = "Invalid IP or subnet. {}", _0)]
+    InvalidIpOrSubnet(String),
+    #[display(fmt = "P2P networking is disabled. {}", _0)]
+    P2pDisabled(String),
+    #[display(fmt = "Method is deprecated. {}", _0)]
+    MethodDeprecated(String),
+    #[display(fmt = "Operation {} was cancelled.", _0)]
+    OperationCancelled(OperationId),
+    #[display(fmt = "Operation {} timed out.", _0)]
+    OperationTimeout(OperationId),
+    #[display(fmt = "Unknown network {}.", _0)]
+    UnknownNetwork(String),
+    #[display(fmt = "Node rejected the RPC credentials with HTTP status {}.", _0)]
+    Unauthorized(u16),
+    #[display(fmt = "Node answered with HTTP status {} and no JSON-RPC response.", _0)]
+    Http(u16),
     #[display(fmt = "{}", _0)]
     Rpc(RpcError),
     #[display(fmt = "{}", _0)]
     Other(io::Error),
 }
 
+impl Fail for Error {}
+
 pub type Result<T> = ::std::result::Result<T, Error>;
 pub type Params = Vec<Value>;
 
@@ -52,6 +175,116 @@ impl Error {
     pub fn incorrect_transaction<S: Into<String>>(s: S) -> Error {
         Error::TransactionIncorrect(s.into())
     }
+
+    /// Returns the JSON-RPC error code, `None` for errors not reported by the node.
+    pub fn code(&self) -> Option<i64> {
+        let code = match *self {
+            Error::MethodNotFound(_) => -32601,
+            Error::InvalidRequest(_) => -32600,
+            Error::InvalidParams(_) => -32602,
+            Error::InternalError(_) => -32603,
+            Error::ParseError(_) => -32700,
+            Error::Misc(_) => -1,
+            Error::ForbiddenBySafeMode(_) => -2,
+            Error::InvalidAmount(_) => -3,
+            Error::WalletError(_) => -4,
+            Error::NoInformation(_) => -5,
+            Error::InsufficientFunds(_) => -6,
+            Error::Memory(_) => -7,
+            Error::InvalidParameter(_) => -8,
+            Error::ClientNotConnected(_) => -9,
+            Error::InInitialDownload(_) => -10,
+            Error::InvalidAccountName(_) => -11,
+            Error::KeypoolRanOut(_) => -12,
+            Error::WalletUnlockNeeded(_) => -13,
+            Error::WalletPassphraseIncorrect(_) => -14,
+            Error::WalletWrongEncState(_) => -15,
+            Error::WalletEncryptionFailed(_) => -16,
+            Error::WalletAlreadyUnlocked(_) => -17,
+            Error::WalletNotFound(_) => -18,
+            Error::WalletNotSpecified(_) => -19,
+            Error::Database(_) => -20,
+            Error::Deserialization(_) => -22,
+            Error::NodeAlreadyAdded(_) => -23,
+            Error::NodeNotAdded(_) => -24,
+            Error::TransactionIncorrect(_) => -25,
+            Error::TransactionRejected(_) => -26,
+            Error::TransactionAlreadyInChain(_) => -27,
+            Error::InWarmup(_) => -28,
+            Error::NodeNotConnected(_) => -29,
+            Error::InvalidIpOrSubnet(_) => -30,
+            Error::P2pDisabled(_) => -31,
+            Error::MethodDeprecated(_) => -32,
+            Error::Rpc(RpcError::Rpc(ref value)) => return value.pointer("/code")?.as_i64(),
+            _ => return None,
+        };
+        Some(code)
+    }
+
+    /// Returns the message reported by the node.
+    pub fn message(&self) -> Option<&str> {
+        match *self {
+            Error::MethodNotFound(ref msg)
+            | Error::InvalidRequest(ref msg)
+            | Error::InvalidParams(ref msg)
+            | Error::InternalError(ref msg)
+            | Error::ParseError(ref msg)
+            | Error::Misc(ref msg)
+            | Error::ForbiddenBySafeMode(ref msg)
+            | Error::InvalidAmount(ref msg)
+            | Error::WalletError(ref msg)
+            | Error::NoInformation(ref msg)
+            | Error::InsufficientFunds(ref msg)
+            | Error::Memory(ref msg)
+            | Error::InvalidParameter(ref msg)
+            | Error::ClientNotConnected(ref msg)
+            | Error::InInitialDownload(ref msg)
+            | Error::InvalidAccountName(ref msg)
+            | Error::KeypoolRanOut(ref msg)
+            | Error::WalletUnlockNeeded(ref msg)
+            | Error::WalletPassphraseIncorrect(ref msg)
+            | Error::WalletWrongEncState(ref msg)
+            | Error::WalletEncryptionFailed(ref msg)
+            | Error::WalletAlreadyUnlocked(ref msg)
+            | Error::WalletNotFound(ref msg)
+            | Error::WalletNotSpecified(ref msg)
+            | Error::Database(ref msg)
+            | Error::Deserialization(ref msg)
+            | Error::NodeAlreadyAdded(ref msg)
+            | Error::NodeNotAdded(ref msg)
+            | Error::TransactionIncorrect(ref msg)
+            | Error::TransactionRejected(ref msg)
+            | Error::TransactionAlreadyInChain(ref msg)
+            | Error::InWarmup(ref msg)
+            | Error::NodeNotConnected(ref msg)
+            | Error::InvalidIpOrSubnet(ref msg)
+            | Error::P2pDisabled(ref msg)
+            | Error::MethodDeprecated(ref msg) => Some(msg),
+            Error::Rpc(RpcError::Rpc(ref value)) => value.pointer("/message")?.as_str(),
+            _ => None,
+        }
+    }
+
+    /// Whether the same call may succeed later: the node is starting up, has no
+    /// peers or is still syncing, or the connection or the HTTP server failed.
+    pub fn is_retryable(&self) -> bool {
+        match *self {
+            Error::InWarmup(_) | Error::ClientNotConnected(_) | Error::InInitialDownload(_) => true,
+            Error::Http(status) => status >= 500,
+            Error::Rpc(RpcError::Client(ref e)) => {
+                e.is_http() || e.is_timeout() || e.is_server_error()
+            }
+            Error::Other(ref e) => matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted
+            ),
+            _ => false,
+        }
+    }
 }
 
 impl From<RpcError> for Error {
@@ -62,18 +295,46 @@ impl From<RpcError> for Error {
                     let msg = value
                         .pointer("/message")
                         .and_then(Value::as_str)
-                        .unwrap_or_else(|| "")
+                        .unwrap_or("")
                         .into();
 
                     match code {
-                        -3 => return Error::InvalidAmount,
+                        -32601 => return Error::MethodNotFound(msg),
+                        -32600 => return Error::InvalidRequest(msg),
+                        -32602 => return Error::InvalidParams(msg),
+                        -32603 => return Error::InternalError(msg),
+                        -32700 => return Error::ParseError(msg),
+                        -1 => return Error::Misc(msg),
+                        -2 => return Error::ForbiddenBySafeMode(msg),
+                        -3 => return Error::InvalidAmount(msg),
                         -4 => return Error::WalletError(msg),
                         -5 => return Error::NoInformation(msg),
-                        -6 => return Error::InsufficientFunds,
+                        -6 => return Error::InsufficientFunds(msg),
                         -7 => return Error::Memory(msg),
+                        -8 => return Error::InvalidParameter(msg),
+                        -9 => return Error::ClientNotConnected(msg),
+                        -10 => return Error::InInitialDownload(msg),
+                        -11 => return Error::InvalidAccountName(msg),
+                        -12 => return Error::KeypoolRanOut(msg),
+                        -13 => return Error::WalletUnlockNeeded(msg),
+                        -14 => return Error::WalletPassphraseIncorrect(msg),
+                        -15 => return Error::WalletWrongEncState(msg),
+                        -16 => return Error::WalletEncryptionFailed(msg),
+                        -17 => return Error::WalletAlreadyUnlocked(msg),
+                        -18 => return Error::WalletNotFound(msg),
+                        -19 => return Error::WalletNotSpecified(msg),
+                        -20 => return Error::Database(msg),
+                        -22 => return Error::Deserialization(msg),
+                        -23 => return Error::NodeAlreadyAdded(msg),
+                        -24 => return Error::NodeNotAdded(msg),
                         -25 => return Error::TransactionIncorrect(msg),
                         -26 => return Error::TransactionRejected(msg),
-                        -27 => return Error::TransactionAlreadyInChain,
+                        -27 => return Error::TransactionAlreadyInChain(msg),
+                        -28 => return Error::InWarmup(msg),
+                        -29 => return Error::NodeNotConnected(msg),
+                        -30 => return Error::InvalidIpOrSubnet(msg),
+                        -31 => return Error::P2pDisabled(msg),
+                        -32 => return Error::MethodDeprecated(msg),
                         _ => {}
                     }
                 }
@@ -84,17 +345,30 @@ impl From<RpcError> for Error {
     }
 }
 
+impl From<OperationError> for Error {
+    fn from(e: OperationError) -> Error {
+        Error::from(RpcError::Rpc(json!({
+            "code": e.code,
+            "message": e.message,
+        })))
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         Error::Other(e)
     }
 }
 
-pub struct Client {
-    inner: RpcClient,
+pub struct Client<T = HttpTransport> {
+    transport: T,
+    nonce: AtomicUsize,
+    network: Mutex<Option<Network>>,
+    redactor: redact::Redactor,
+    retry: RetryPolicy,
 }
 
-impl std::fmt::Debug for Client {
+impl<T> std::fmt::Debug for Client<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("BitcoinRpcClient").finish()
     }
@@ -105,7 +379,7 @@ pub struct Info {
     pub version: u32,
     pub protocolversion: u32,
     pub walletversion: u32,
-    pub balance: f64,
+    pub balance: Amount,
     pub blocks: u64,
     pub timeoffset: u64,
     pub connections: u32,
@@ -114,8 +388,8 @@ pub struct Info {
     pub testnet: bool,
     pub keypoololdest: u64,
     pub keypoolsize: u64,
-    pub paytxfee: f64,
-    pub relayfee: f64,
+    pub paytxfee: Amount,
+    pub relayfee: Amount,
     pub errors: String,
 }
 
@@ -200,20 +474,29 @@ pub struct ScriptPubKey {
     pub addresses: Option<Vec<String>>,
 }
 
+/// Transaction input; coinbase inputs have `coinbase` instead of the spent outpoint and
+/// `scriptSig`.
 // TODO use TxIn from bitcoin crate
 #[derive(Clone, Deserialize, Debug)]
 pub struct TxIn {
-    pub txid: String,
-    pub vout: u32,
+    pub txid: Option<String>,
+    pub vout: Option<u32>,
     #[serde(rename = "scriptSig")]
-    pub script_sig: ScriptSig,
+    pub script_sig: Option<ScriptSig>,
+    pub coinbase: Option<String>,
     pub sequence: u64,
     pub txinwitness: Option<Vec<String>>,
 }
 
+impl TxIn {
+    pub fn is_coinbase(&self) -> bool {
+        self.coinbase.is_some()
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct TxOut {
-    pub value: f64,
+    pub value: Amount,
     pub n: u32,
     #[serde(rename = "scriptPubKey")]
     pub script_pubkey: ScriptPubKey,
@@ -236,21 +519,21 @@ pub struct RawTransactionInfo {
 #[derive(Clone, Deserialize, Debug)]
 pub struct TransactionDetails {
     #[serde(rename = "involvesWatchonly")]
-    involves_watchonly: Option<bool>,
-    account: String,
-    address: Option<String>,
-    category: String,
-    amount: f64,
-    vout: u32,
-    fee: Option<f64>,
-    abandoned: Option<bool>,
-    label: Option<String>,
+    pub involves_watchonly: Option<bool>,
+    pub account: String,
+    pub address: Option<String>,
+    pub category: String,
+    pub amount: Amount,
+    pub vout: u32,
+    pub fee: Option<Amount>,
+    pub abandoned: Option<bool>,
+    pub label: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct WalletTransactionInfo {
-    pub amount: f64,
-    pub fee: Option<f64>,
+    pub amount: Amount,
+    pub fee: Option<Amount>,
     pub confirmations: u64,
     pub generated: Option<bool>,
     pub blockhash: Option<String>,
@@ -270,8 +553,8 @@ pub struct WalletTransactionInfo {
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct ZecWalletTransactionInfo {
-    pub amount: f64,
-    pub fee: Option<f64>,
+    pub amount: Amount,
+    pub fee: Option<Amount>,
     pub confirmations: u64,
     pub generated: Option<bool>,
     pub blockhash: Option<String>,
@@ -282,23 +565,307 @@ pub struct ZecWalletTransactionInfo {
     pub walletconflicts: Vec<String>,
     pub time: u64,
     pub timereceived: u64,
-    pub vjoinsplit: Vec<String>,
+    pub vjoinsplit: Vec<JoinSplit>,
     pub details: Vec<TransactionDetails>,
     pub hex: String
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct JoinSplit {
+    pub vpub_old: Amount,
+    #[serde(rename = "vpub_oldZat", default, deserialize_with = "amount::deserialize_opt_sat")]
+    pub vpub_old_zat: Option<Amount>,
+    pub vpub_new: Amount,
+    #[serde(rename = "vpub_newZat", default, deserialize_with = "amount::deserialize_opt_sat")]
+    pub vpub_new_zat: Option<Amount>,
+    pub anchor: String,
+    pub nullifiers: Vec<String>,
+    pub commitments: Vec<String>,
+    #[serde(rename = "onetimePubKey")]
+    pub onetime_pubkey: Option<String>,
+    #[serde(rename = "randomSeed")]
+    pub random_seed: Option<String>,
+    pub macs: Vec<String>,
+    pub proof: Option<String>,
+    #[serde(default)]
+    pub ciphertexts: Vec<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct SaplingSpend {
+    pub cv: String,
+    pub anchor: String,
+    pub nullifier: String,
+    pub rk: String,
+    pub proof: String,
+    #[serde(rename = "spendAuthSig")]
+    pub spend_auth_sig: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct SaplingOutput {
+    pub cv: String,
+    pub cmu: String,
+    #[serde(rename = "ephemeralKey")]
+    pub ephemeral_key: String,
+    #[serde(rename = "encCiphertext")]
+    pub enc_ciphertext: String,
+    #[serde(rename = "outCiphertext")]
+    pub out_ciphertext: String,
+    pub proof: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct OrchardAction {
+    pub cv: String,
+    pub nullifier: String,
+    pub rk: String,
+    pub cmx: String,
+    #[serde(rename = "ephemeralKey")]
+    pub ephemeral_key: String,
+    #[serde(rename = "encCiphertext")]
+    pub enc_ciphertext: String,
+    #[serde(rename = "outCiphertext")]
+    pub out_ciphertext: String,
+    #[serde(rename = "spendAuthSig")]
+    pub spend_auth_sig: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct OrchardFlags {
+    #[serde(rename = "enableSpends")]
+    pub enable_spends: bool,
+    #[serde(rename = "enableOutputs")]
+    pub enable_outputs: bool,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct OrchardBundle {
+    pub actions: Vec<OrchardAction>,
+    #[serde(rename = "valueBalance")]
+    pub value_balance: Amount,
+    #[serde(rename = "valueBalanceZat", deserialize_with = "amount::deserialize_sat")]
+    pub value_balance_zat: Amount,
+    pub flags: Option<OrchardFlags>,
+    pub anchor: Option<String>,
+    pub proof: Option<String>,
+    #[serde(rename = "bindingSig")]
+    pub binding_sig: Option<String>,
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct ZecRawTransactionInfo {
     pub hex: Option<String>,
     pub txid: String,
+    pub authdigest: Option<String>,
+    pub size: Option<u64>,
     pub overwintered: bool,
     pub version: u32,
-    pub versiongroupid: String,
-    pub expiryheight: u32,
+    pub versiongroupid: Option<String>,
+    pub expiryheight: Option<u32>,
     pub locktime: u32,
     pub vin: Vec<TxIn>,
     pub vout: Vec<TxOut>,
-    pub vjoinsplit: Vec<String>,
+    #[serde(default)]
+    pub vjoinsplit: Vec<JoinSplit>,
+    #[serde(rename = "joinSplitPubKey")]
+    pub join_split_pubkey: Option<String>,
+    #[serde(rename = "joinSplitSig")]
+    pub join_split_sig: Option<String>,
+    #[serde(rename = "valueBalance")]
+    pub value_balance: Option<Amount>,
+    #[serde(rename = "valueBalanceZat", default, deserialize_with = "amount::deserialize_opt_sat")]
+    pub value_balance_zat: Option<Amount>,
+    #[serde(rename = "vShieldedSpend", default)]
+    pub shielded_spends: Vec<SaplingSpend>,
+    #[serde(rename = "vShieldedOutput", default)]
+    pub shielded_outputs: Vec<SaplingOutput>,
+    #[serde(rename = "bindingSig")]
+    pub binding_sig: Option<String>,
+    pub orchard: Option<OrchardBundle>,
+    pub blockhash: Option<String>,
+    pub height: Option<u64>,
+    pub confirmations: Option<u64>,
+    pub time: Option<u64>,
+    pub blocktime: Option<u64>,
+}
+
+/// Block returned by `getblock`; `T` is a txid, or a decoded transaction with verbosity 2.
+///
+/// `confirmations` is -1 for blocks off the main chain.
+#[derive(Clone, Deserialize, Debug)]
+pub struct BlockInfo<T = String> {
+    pub hash: String,
+    pub confirmations: i64,
+    pub size: u64,
+    pub strippedsize: Option<u64>,
+    pub weight: Option<u64>,
+    pub height: u64,
+    pub version: u32,
+    #[serde(rename = "versionHex")]
+    pub version_hex: Option<String>,
+    pub merkleroot: String,
+    pub blockcommitments: Option<String>,
+    pub authdataroot: Option<String>,
+    pub finalsaplingroot: Option<String>,
+    pub finalorchardroot: Option<String>,
+    pub chainhistoryroot: Option<String>,
+    pub anchor: Option<String>,
+    pub tx: Vec<T>,
+    #[serde(rename = "nTx")]
+    pub n_tx: Option<u64>,
+    pub time: u64,
+    pub mediantime: Option<u64>,
+    pub nonce: BlockNonce,
+    pub solution: Option<String>,
+    pub bits: String,
+    pub difficulty: f64,
+    pub chainwork: String,
+    #[serde(rename = "chainSupply")]
+    pub chain_supply: Option<ValuePool>,
+    #[serde(rename = "valuePools", default)]
+    pub value_pools: Vec<ValuePool>,
+    pub previousblockhash: Option<String>,
+    pub nextblockhash: Option<String>,
+}
+
+/// Header nonce, a number on Bitcoin and a 256-bit hex string on Zcash.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum BlockNonce {
+    Number(u32),
+    Hex(String),
+}
+
+/// Value held in a pool (`transparent`, `sprout`, `sapling`, `orchard`, ...) up to a block.
+///
+/// `id` is missing on the total `chainSupply`.
+#[derive(Clone, Deserialize, Debug)]
+pub struct ValuePool {
+    pub id: Option<String>,
+    pub monitored: bool,
+    #[serde(rename = "chainValue")]
+    pub chain_value: Option<Amount>,
+    #[serde(rename = "chainValueZat", default, deserialize_with = "amount::deserialize_opt_sat")]
+    pub chain_value_zat: Option<Amount>,
+    #[serde(rename = "valueDelta")]
+    pub value_delta: Option<Amount>,
+    #[serde(rename = "valueDeltaZat", default, deserialize_with = "amount::deserialize_opt_sat")]
+    pub value_delta_zat: Option<Amount>,
+}
+
+/// Header returned by `getblockheader` in verbose mode.
+#[derive(Clone, Deserialize, Debug)]
+pub struct BlockHeaderInfo {
+    pub hash: String,
+    pub confirmations: i64,
+    pub height: u64,
+    pub version: u32,
+    #[serde(rename = "versionHex")]
+    pub version_hex: Option<String>,
+    pub merkleroot: String,
+    pub finalsaplingroot: Option<String>,
+    pub time: u64,
+    pub mediantime: Option<u64>,
+    pub nonce: BlockNonce,
+    pub solution: Option<String>,
+    pub bits: String,
+    pub difficulty: f64,
+    pub chainwork: String,
+    #[serde(rename = "nTx")]
+    pub n_tx: Option<u64>,
+    pub previousblockhash: Option<String>,
+    pub nextblockhash: Option<String>,
+}
+
+/// Chain state returned by `getblockchaininfo`; Zcash and Bitcoin specific fields are optional.
+#[derive(Clone, Deserialize, Debug)]
+pub struct BlockchainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    pub headers: u64,
+    pub bestblockhash: String,
+    pub difficulty: f64,
+    pub verificationprogress: f64,
+    pub chainwork: String,
+    pub mediantime: Option<u64>,
+    pub initialblockdownload: Option<bool>,
+    pub initial_block_download_complete: Option<bool>,
+    pub estimatedheight: Option<u64>,
+    pub pruned: Option<bool>,
+    pub size_on_disk: Option<u64>,
+    pub commitments: Option<u64>,
+    #[serde(rename = "chainSupply")]
+    pub chain_supply: Option<ValuePool>,
+    #[serde(rename = "valuePools", default)]
+    pub value_pools: Vec<ValuePool>,
+    /// Network upgrades keyed by hex consensus branch id.
+    pub upgrades: Option<BTreeMap<String, UpgradeInfo>>,
+    pub consensus: Option<ConsensusInfo>,
+}
+
+impl BlockchainInfo {
+    /// Network of the node, a Zcash one if upgrades are reported.
+    pub fn network(&self) -> Option<Network> {
+        Network::from_chain_name(&self.chain, self.upgrades.is_some())
+    }
+
+    /// Whether the node is done syncing, on either coin.
+    pub fn is_synced(&self) -> bool {
+        match (self.initial_block_download_complete, self.initialblockdownload) {
+            (Some(complete), _) => complete,
+            (None, Some(initial)) => !initial,
+            (None, None) => self.blocks == self.headers,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct UpgradeInfo {
+    pub name: String,
+    pub activationheight: u64,
+    pub status: String,
+    pub info: String,
+}
+
+/// Consensus branch ids in hex, of the chain tip and of the next block.
+#[derive(Clone, Deserialize, Debug)]
+pub struct ConsensusInfo {
+    pub chaintip: String,
+    pub nextblock: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ChainTip {
+    pub height: u64,
+    pub hash: String,
+    pub branchlen: u64,
+    pub status: ChainTipStatus,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChainTipStatus {
+    Active,
+    ValidFork,
+    ValidHeaders,
+    HeadersOnly,
+    Invalid,
+}
+
+/// UTXO set statistics returned by `gettxoutsetinfo`.
+#[derive(Clone, Deserialize, Debug)]
+pub struct TxOutSetInfo {
+    pub height: u64,
+    pub bestblock: String,
+    pub transactions: Option<u64>,
+    pub txouts: u64,
+    pub bytes_serialized: Option<u64>,
+    pub bogosize: Option<u64>,
+    pub hash_serialized: Option<String>,
+    pub hash_serialized_2: Option<String>,
+    pub disk_size: Option<u64>,
+    pub total_amount: Amount,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -319,7 +886,7 @@ pub struct UnspentTransactionInfo {
     pub script_pubkey: String,
     #[serde(rename = "redeemScript")]
     pub redeem_script: Option<String>,
-    pub amount: f64,
+    pub amount: Amount,
     pub confirmations: u64,
     pub spendable: bool,
     #[serde(skip)]
@@ -336,7 +903,7 @@ pub struct UnspentTransactionInfoZec {
     pub script_pubkey: String,
     #[serde(rename = "redeemScript")]
     pub redeem_script: Option<String>,
-    pub amount: f64,
+    pub amount: Amount,
     pub confirmations: u64,
     pub spendable: bool,
 }
@@ -347,7 +914,7 @@ pub struct ReceivedByAddress {
     pub involves_watchonly: Option<bool>,
     pub address: String,
     pub account: String,
-    pub amount: f64,
+    pub amount: Amount,
     pub confirmations: u64,
     pub label: Option<String>,
     pub txids: Vec<String>,
@@ -390,7 +957,16 @@ pub struct TransactionInput {
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct TransactionOutput {
     pub address: String,
-    pub value: String,
+    pub value: Amount,
+}
+
+impl TransactionOutput {
+    pub fn new<A: AsRef<str>>(address: A, value: Amount) -> TransactionOutput {
+        TransactionOutput {
+            address: address.as_ref().to_owned(),
+            value,
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -403,332 +979,555 @@ pub struct TransactionInfo {
     pub time: u64,
 }
 
-#[derive(Debug)]
-struct RpcRequest {
-    method: String,
-    params: Params,
-    response: Result<Value>,
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ShieldedAddressType {
+    Sprout,
+    Sapling,
 }
 
-impl Client {
-    pub fn new<S>(url: S, user: Option<String>, password: Option<String>) -> Client
-    where
-        S: Into<String>,
-    {
-        Client {
-            inner: RpcClient::new(url.into(), user.map(Into::into), password.map(Into::into)),
-        }
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct ZValidateAddressInfo {
+    pub isvalid: bool,
+    pub address: Option<String>,
+    pub address_type: Option<String>,
+    pub ismine: Option<bool>,
+    pub payingkey: Option<String>,
+    pub transmissionkey: Option<String>,
+    pub diversifier: Option<String>,
+    pub diversifiedtransmissionkey: Option<String>,
+}
 
-    pub fn url(&self) -> &str {
-        self.inner.url()
-    }
-    pub fn password(&self) -> &Option<String> {
-        self.inner.password()
-    }
-    pub fn username(&self) -> &Option<String> {
-        self.inner.username()
-    }
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceiverType {
+    P2pkh,
+    Sapling,
+    Orchard,
+}
 
-    fn request<T>(&self, method: &str, params: Params) -> Result<T>
-    where
-        for<'de> T: Deserialize<'de>,
-    {
-        let request = self.inner.build_request(method.into(), params);
-        let response = self.inner.send_request(&request)?;
-        trace!(
-            "{:#?}",
-            RpcRequest {
-                method: request.method.clone(),
-                params: request.params.clone(),
-                response: response.clone().into_result::<Value>().map_err(Error::from),
-            }
-        );
-        response.into_result::<T>().map_err(Error::from)
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct NewAccount {
+    pub account: u32,
 }
 
-// public api part
-impl Client {
-    pub fn getinfo(&self) -> Result<Info> {
-        self.request("getinfo", Vec::new())
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct AccountAddress {
+    pub account: u32,
+    pub diversifier_index: u64,
+    pub receiver_types: Vec<ReceiverType>,
+    pub address: UnifiedAddress,
+}
 
-    pub fn getnewaddress(&self, account: &str) -> Result<String> {
-        self.request("getnewaddress", vec![Value::String(account.to_owned())])
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct AccountAddressInfo {
+    pub diversifier_index: u64,
+    pub ua: UnifiedAddress,
+}
 
-    pub fn getnewaddress_legacy(&self, account: &str) -> Result<String> {
-        self.request(
-            "getnewaddress",
-            vec![
-                Value::String(account.to_owned()),
-                Value::String("legacy".to_string()),
-            ],
-        )
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct AccountInfo {
+    pub account: u32,
+    pub addresses: Vec<AccountAddressInfo>,
+}
 
-    pub fn validateaddress(&self, addr: &str) -> Result<ValidateAddressInfo> {
-        self.request("validateaddress", vec![Value::String(addr.to_owned())])
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct UnifiedReceivers {
+    pub p2pkh: Option<Address>,
+    pub p2sh: Option<Address>,
+    pub sapling: Option<SaplingAddress>,
+    pub orchard: Option<UnifiedAddress>,
+}
 
-    pub fn getaddressinfo(&self, addr: &str) -> Result<AddressInfo> {
-        self.request("getaddressinfo", vec![Value::String(addr.to_owned())])
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct PoolBalance {
+    #[serde(rename = "valueZat", deserialize_with = "amount::deserialize_sat")]
+    pub value: Amount,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct AccountPools {
+    pub transparent: Option<PoolBalance>,
+    pub sapling: Option<PoolBalance>,
+    pub orchard: Option<PoolBalance>,
+}
 
-    pub fn createmultisig<V: AsRef<[String]>>(&self, signs: u8, addrs: V) -> Result<MultiSig> {
-        let n = serde_json::to_value(signs).unwrap();
-        let addrs = serde_json::to_value(addrs.as_ref()).unwrap();
-        self.request("createmultisig", vec![n, addrs])
+#[derive(Clone, Deserialize, Debug)]
+pub struct AccountBalance {
+    pub pools: AccountPools,
+    pub minimum_confirmations: u32,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ShieldCoinbaseResult {
+    #[serde(rename = "remainingUTXOs")]
+    pub remaining_utxos: u64,
+    #[serde(rename = "remainingValue")]
+    pub remaining_value: Amount,
+    #[serde(rename = "shieldingUTXOs")]
+    pub shielding_utxos: u64,
+    #[serde(rename = "shieldingValue")]
+    pub shielding_value: Amount,
+    pub opid: OperationId,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct MergeToAddressResult {
+    #[serde(rename = "remainingUTXOs")]
+    pub remaining_utxos: u64,
+    #[serde(rename = "remainingTransparentValue")]
+    pub remaining_transparent_value: Amount,
+    #[serde(rename = "remainingNotes")]
+    pub remaining_notes: u64,
+    #[serde(rename = "remainingShieldedValue")]
+    pub remaining_shielded_value: Amount,
+    #[serde(rename = "mergingUTXOs")]
+    pub merging_utxos: u64,
+    #[serde(rename = "mergingTransparentValue")]
+    pub merging_transparent_value: Amount,
+    #[serde(rename = "mergingNotes")]
+    pub merging_notes: u64,
+    #[serde(rename = "mergingShieldedValue")]
+    pub merging_shielded_value: Amount,
+    pub opid: OperationId,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Rescan {
+    Yes,
+    No,
+    WhenKeyIsNew,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct OperationId(String);
+
+impl OperationId {
+    pub fn as_str(&self) -> &str {
+        &self.0
     }
+}
 
-    pub fn sendtoaddress(&self, addr: &str, amount: &str) -> Result<String> {
-        let params = vec![
-            serde_json::to_value(addr).unwrap(),
-            serde_json::to_value(amount).unwrap(),
-        ];
-        self.request("sendtoaddress", params)
+impl From<String> for OperationId {
+    fn from(s: String) -> OperationId {
+        OperationId(s)
     }
+}
 
-    pub fn getrawtransaction(&self, txid: &str) -> Result<String> {
-        let params = json!([txid, 0]).as_array().cloned().unwrap();
-        self.request("getrawtransaction", params)
+impl std::fmt::Display for OperationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
     }
+}
 
-    pub fn getrawtransaction_verbose(&self, txid: &str) -> Result<RawTransactionInfo> {
-        let params = json!([txid, 1]).as_array().cloned().unwrap();
-        self.request("getrawtransaction", params)
+#[derive(Clone, Serialize, Debug)]
+pub struct SendManyRecipient {
+    pub address: String,
+    pub amount: Amount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+impl SendManyRecipient {
+    pub fn new<A: AsRef<str>>(address: A, amount: Amount, memo: Option<String>) -> SendManyRecipient {
+        SendManyRecipient {
+            address: address.as_ref().to_owned(),
+            amount,
+            memo,
+        }
     }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationState {
+    Queued,
+    Executing,
+    Success,
+    Failed,
+    Cancelled,
+}
 
-    pub fn getrawtransaction_verbose_zec(&self, txid: &str) -> Result<ZecRawTransactionInfo> {
-        let params = json!([txid, 1]).as_array().cloned().unwrap();
-        self.request("getrawtransaction", params)
+impl OperationState {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, OperationState::Queued | OperationState::Executing)
     }
+}
 
-    pub fn createrawtransaction<T, O>(
-        &self,
-        transactions: T,
-        outputs: O,
-        data: Option<String>,
-    ) -> Result<String>
-    where
-        T: AsRef<[TransactionInput]>,
-        O: AsRef<[TransactionOutput]>,
-    {
-        let mut map = BTreeMap::new();
-        map.extend(
-            outputs
-                .as_ref()
-                .iter()
-                .map(|x| (x.address.clone(), x.value.clone())),
-        );
-        if let Some(data) = data {
-            map.insert("data".into(), data);
-        }
+#[derive(Clone, Deserialize, Debug)]
+pub struct OperationError {
+    pub code: i64,
+    pub message: String,
+}
 
-        let params = json!([transactions.as_ref(), map])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("createrawtransaction", params)
+#[derive(Clone, Deserialize, Debug)]
+pub struct OperationResult {
+    pub txid: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct OperationStatus {
+    pub id: OperationId,
+    pub status: OperationState,
+    pub creation_time: u64,
+    pub method: Option<String>,
+    pub params: Option<Value>,
+    pub result: Option<OperationResult>,
+    pub error: Option<OperationError>,
+    pub execution_secs: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct TotalBalance {
+    pub transparent: Amount,
+    pub private: Amount,
+    pub total: Amount,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ShieldedPool {
+    Sprout,
+    Sapling,
+    Orchard,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ShieldedNote {
+    pub txid: String,
+    pub pool: Option<ShieldedPool>,
+    pub jsindex: Option<u32>,
+    pub jsoutindex: Option<u32>,
+    pub outindex: Option<u32>,
+    pub confirmations: Option<u64>,
+    pub spendable: Option<bool>,
+    pub address: Option<ShieldedAddress>,
+    pub amount: Amount,
+    #[serde(rename = "amountZat", default, deserialize_with = "amount::deserialize_opt_sat")]
+    pub amount_zat: Option<Amount>,
+    pub memo: String,
+    #[serde(rename = "memoStr")]
+    pub memo_str: Option<String>,
+    pub change: Option<bool>,
+    pub blockheight: Option<u64>,
+    pub blockindex: Option<u64>,
+    pub blocktime: Option<u64>,
+}
+
+impl ShieldedNote {
+    pub fn memo_bytes(&self) -> Option<Vec<u8>> {
+        hex::decode(&self.memo).ok()
     }
 
-    pub fn dumpprivkey(&self, pub_key: &str) -> Result<String> {
-        let params = json!([pub_key]).as_array().cloned().unwrap();
-        self.request("dumpprivkey", params)
+    /// Returns the memo as text if it is a ZIP 302 text memo.
+    pub fn memo_text(&self) -> Option<String> {
+        if let Some(ref memo) = self.memo_str {
+            return Some(memo.clone());
+        }
+        let bytes = self.memo_bytes()?;
+        match bytes.first() {
+            Some(&first) if first <= 0xF4 => {
+                let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+                String::from_utf8(bytes[..len].to_vec()).ok()
+            }
+            _ => None,
+        }
     }
+}
 
-    pub fn signrawtransaction<O, K>(
-        &self,
-        txhex: &str,
-        outputs: O,
-        priv_keys: K,
-    ) -> Result<SignTxOutput>
+impl Client {
+    pub fn new<S>(url: S, user: Option<String>, password: Option<String>) -> Client
     where
-        O: AsRef<[DependentOutput]>,
-        K: AsRef<[String]>,
+        S: Into<String>,
     {
-        let params = json!([txhex, outputs.as_ref(), priv_keys.as_ref()])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("signrawtransaction", params)
+        Client::with_transport(HttpTransport::new(url, user, password))
     }
 
-    pub fn signrawtransaction_all<O, K>(
-        &self,
-        txhex: &str,
-        outputs: O,
-        priv_keys: K,
-    ) -> Result<SignTxOutput>
-    where
-        O: AsRef<[DependentOutput]>,
-        K: AsRef<[String]>,
-    {
-        let params = json!([
-            txhex,
-            outputs.as_ref(),
-            priv_keys.as_ref(),
-            "ALL".to_string()
-        ]).as_array()
-        .cloned()
-        .unwrap();
-        self.request("signrawtransaction", params)
+    pub fn url(&self) -> &str {
+        self.transport.url()
+    }
+    pub fn password(&self) -> &Option<String> {
+        self.transport.password()
     }
+    pub fn username(&self) -> &Option<String> {
+        self.transport.username()
+    }
+}
 
-    pub fn sendrawtransaction(&self, txhex: &str) -> Result<String> {
-        self.request(
-            "sendrawtransaction",
-            vec![serde_json::to_value(txhex).unwrap()],
-        )
+impl<T: Transport> Client<T> {
+    /// Creates a client sending requests through `transport`.
+    pub fn with_transport(transport: T) -> Client<T> {
+        Client {
+            transport,
+            nonce: AtomicUsize::new(0),
+            network: Mutex::new(None),
+            redactor: redact::Redactor::default(),
+            retry: RetryPolicy::none(),
+        }
     }
 
-    pub fn decoderawtransaction(&self, txhex: &str) -> Result<RawTransactionInfo> {
-        self.request(
-            "decoderawtransaction",
-            vec![serde_json::to_value(txhex).unwrap()],
-        )
+    pub fn transport(&self) -> &T {
+        &self.transport
     }
 
-    pub fn addwitnessaddress(&self, addr: &str) -> Result<String> {
-        self.request(
-            "addwitnessaddress",
-            vec![serde_json::to_value(addr).unwrap()],
-        )
+    /// Repeats calls failing with transient errors, e.g. while the node is warming up.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Client<T> {
+        self.retry = retry;
+        self
     }
 
-    pub fn listtransactions(
-        &self,
-        count: u32,
-        from: u32,
-        include_watch_only: bool,
-    ) -> Result<Vec<TransactionInfo>> {
-        let params = json!(["*", count, from, include_watch_only])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("listtransactions", params)
-    }
-
-    pub fn listunspent<V: AsRef<str> + Serialize>(
-        &self,
-        min_confirmations: u32,
-        max_confirmations: u32,
-        addresses: &[V],
-    ) -> Result<Vec<UnspentTransactionInfo>> {
-        let params = json!([min_confirmations, max_confirmations, addresses])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("listunspent", params)
-    }
-
-    pub fn listunspent_zcash<V: AsRef<str> + Serialize>(
-        &self,
-        min_confirmations: u32,
-        max_confirmations: u32,
-        addresses: &[V],
-    ) -> Result<Vec<UnspentTransactionInfoZec>> {
-        let params = json!([min_confirmations, max_confirmations, addresses])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("listunspent", params)
-    }
-
-    pub fn importaddress(&self, addr: &str, label: &str, rescan: bool, p2sh: bool) -> Result<()> {
-        let params = json!([addr, label, rescan, p2sh])
-            .as_array()
-            .cloned()
-            .unwrap();
-        // special case for decode {"result":null}
-        let r: Result<Option<bool>> = self.request("importaddress", params);
-        match r {
-            Ok(_) | Err(Error::Rpc(RpcError::NoErrorOrResult)) => Ok(()),
-            Err(e) => Err(e),
-        }
+    /// Masks parts of `method` calls in trace logs, in addition to the built-in
+    /// private key and passphrase methods.
+    pub fn with_redaction<S>(mut self, method: S, redaction: Redaction) -> Client<T>
+    where
+        S: Into<String>,
+    {
+        self.redactor.insert(method.into(), redaction);
+        self
     }
 
-    pub fn importaddress_zcash(&self, addr: &str, label: &str, rescan: bool) -> Result<()> {
-        let params = json!([addr, label, rescan]).as_array().cloned().unwrap();
-        // special case for decode {"result":null}
-        let r: Result<Option<bool>> = self.request("importaddress", params);
-        match r {
-            Ok(_) | Err(Error::Rpc(RpcError::NoErrorOrResult)) => Ok(()),
-            Err(e) => Err(e),
-        }
+    /// Sets the network instead of detecting it from the node.
+    pub fn with_network(self, network: Network) -> Client<T> {
+        *self.network.lock().unwrap() = Some(network);
+        self
     }
 
-    pub fn generate(&self, nblocks: u64, maxtries: u64) -> Result<Vec<String>> {
-        let params = json!([nblocks, maxtries]).as_array().cloned().unwrap();
-        self.request("generate", params)
+    /// Returns the network of the node, detected with `getblockchaininfo` on first use.
+    pub fn network(&self) -> Result<Network> {
+        if let Some(network) = *self.network.lock().unwrap() {
+            return Ok(network);
+        }
+        let network = self.detect_network()?;
+        *self.network.lock().unwrap() = Some(network);
+        Ok(network)
     }
 
-    pub fn generatetoaddress(
-        &self,
-        nblocks: u64,
-        addr: &str,
-        maxtries: u64,
-    ) -> Result<Vec<String>> {
-        let params = json!([nblocks, addr, maxtries])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("generatetoaddress", params)
+    fn detect_network(&self) -> Result<Network> {
+        let info = self.getblockchaininfo()?;
+        info.network().ok_or(Error::UnknownNetwork(info.chain))
     }
 
-    pub fn stop(&self) -> Result<String> {
-        self.request("stop", vec![])
+    fn request<R>(&self, method: &str, params: Params) -> Result<R>
+    where
+        for<'de> R: Deserialize<'de>,
+    {
+        let mut attempt = 1;
+        loop {
+            match self.request_once(method, params.clone()) {
+                Err(ref e) if self.retry.should_retry(method, attempt, e) => {
+                    let delay = self.retry.delay(attempt);
+                    debug!("{} failed: {}, retrying in {:?}", method, e, delay);
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
 
-    pub fn getreceivedbyaddress(&self, addr: &str, minconf: u64) -> Result<f64> {
-        let params = json!([addr, minconf]).as_array().cloned().unwrap();
-        self.request("getreceivedbyaddress", params)
+    fn request_once<R>(&self, method: &str, params: Params) -> Result<R>
+    where
+        for<'de> R: Deserialize<'de>,
+    {
+        let request = self.build_request(method, params);
+        let response = self.transport.send_request(&request)?;
+        if response.id != request.id {
+            return Err(Error::Rpc(RpcError::NonceMismatch));
+        }
+        self.trace_request(&request, &response);
+        response.into_result::<R>().map_err(Error::from)
     }
 
-    pub fn gettransaction(&self, txid: &str) -> Result<WalletTransactionInfo> {
-        let params = json!([txid]).as_array().cloned().unwrap();
-        self.request("gettransaction", params)
+    pub(crate) fn build_request(&self, method: &str, params: Params) -> Request {
+        let nonce = self.nonce.fetch_add(1, Ordering::Relaxed) + 1;
+        Request {
+            method: method.to_owned(),
+            params,
+            id: json!(nonce),
+        }
     }
 
-    pub fn gettransaction_zec(&self, txid: &str) -> Result<ZecWalletTransactionInfo> {
-        let params = json!([txid]).as_array().cloned().unwrap();
-        self.request("gettransaction", params)
+    // For methods answering `{"result":null}` on success.
+    pub(crate) fn request_null(&self, method: &str, params: Params) -> Result<()> {
+        let r: Result<Option<Value>> = self.request(method, params);
+        match r {
+            Ok(_) | Err(Error::Rpc(RpcError::NoErrorOrResult)) => Ok(()),
+            Err(e) => Err(e),
+        }
     }
 
-    pub fn getblockcount(&self) -> Result<u64> {
-        self.request("getblockcount", vec![])
+    fn trace_request(&self, request: &Request, response: &Response) {
+        trace_request(&self.redactor, request, response);
     }
+}
 
-    pub fn getbestblockhash(&self) -> Result<String> {
-        self.request("getbestblockhash", vec![])
+pub(crate) fn trace_request(redactor: &redact::Redactor, request: &Request, response: &Response) {
+    if !log_enabled!(::log::Level::Trace) {
+        return;
     }
+    let method = &request.method;
+    let params = redactor.params(method, &request.params);
+    let response = response
+        .clone()
+        .into_result::<Value>()
+        .map(|result| redactor.result(method, result))
+        .map_err(Error::from);
+    trace!(
+        "RpcRequest {{ method: {:?}, params: {:#?}, response: {:#?} }}",
+        method,
+        params,
+        response
+    );
+}
 
-    pub fn getblockhash(&self, height: u64) -> Result<String> {
-        let params = json!([height]).as_array().cloned().unwrap();
-        self.request("getblockhash", params)
+// Outcome of a finished operation, as returned by `wait_for_operation`.
+fn operation_txid(id: &OperationId, status: OperationStatus) -> Result<String> {
+    match (status.status, status.result, status.error) {
+        (OperationState::Success, Some(result), _) => Ok(result.txid),
+        (OperationState::Failed, _, Some(error)) => Err(error.into()),
+        (OperationState::Cancelled, _, _) => Err(Error::OperationCancelled(status.id)),
+        _ => Err(Error::NoInformation(format!(
+            "Operation {} finished without result",
+            id
+        ))),
     }
+}
+
+// public api part
+impl<T: Transport> Client<T> {
+    rpc_methods!(Result);
+
+    /// Polls an asynchronous operation until it finishes and returns the resulting txid.
+    ///
+    /// The finished operation is removed from the node with `z_getoperationresult`.
+    /// A failed operation is converted into the `Error` its RPC error code maps to.
+    pub fn wait_for_operation(
+        &self,
+        id: &OperationId,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<String> {
+        let ids = [id.clone()];
+        let started = Instant::now();
+        loop {
+            let status = self
+                .z_getoperationstatus(&ids)?
+                .into_iter()
+                .find(|status| status.id == *id)
+                .ok_or_else(|| Error::NoInformation(format!("Unknown operation {}", id)))?;
+
+            if status.status.is_finished() {
+                let status = self
+                    .z_getoperationresult(&ids)?
+                    .into_iter()
+                    .find(|status| status.id == *id)
+                    .unwrap_or(status);
+                return operation_txid(id, status);
+            }
 
-    pub fn getblock<S: AsRef<str> + Serialize>(&self, hash: S) -> Result<String> {
-        let params = json!([hash.as_ref(), 0]).as_array().cloned().unwrap();
-        self.request("getblock", params)
+            if started.elapsed() >= timeout {
+                return Err(Error::OperationTimeout(id.clone()));
+            }
+            thread::sleep(poll_interval);
+        }
     }
+}
 
-    pub fn decodescript(&self, script: &str) -> Result<ScriptInfo> {
-        let params = json!([script]).as_array().cloned().unwrap();
-        self.request("decodescript", params)
+#[cfg(test)]
+mod tests {
+    use super::{BlockInfo, BlockNonce, RawTransactionInfo, TxIn};
+    use Amount;
+
+    // `getblock` of the Bitcoin genesis block with verbosity 2, as returned by bitcoind 0.21.
+    const GENESIS_BLOCK: &str = r#"{
+        "hash": "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+        "confirmations": 810000,
+        "strippedsize": 285,
+        "size": 285,
+        "weight": 1140,
+        "height": 0,
+        "version": 1,
+        "versionHex": "00000001",
+        "merkleroot": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+        "tx": [
+            {
+                "txid": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+                "hash": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+                "version": 1,
+                "size": 204,
+                "vsize": 204,
+                "weight": 816,
+                "locktime": 0,
+                "vin": [
+                    {
+                        "coinbase": "04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73",
+                        "sequence": 4294967295
+                    }
+                ],
+                "vout": [
+                    {
+                        "value": 50.00000000,
+                        "n": 0,
+                        "scriptPubKey": {
+                            "asm": "04678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5f OP_CHECKSIG",
+                            "hex": "4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac",
+                            "reqSigs": 1,
+                            "type": "pubkey",
+                            "addresses": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"]
+                        }
+                    }
+                ],
+                "hex": "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000"
+            }
+        ],
+        "time": 1231006505,
+        "mediantime": 1231006505,
+        "nonce": 2083236893,
+        "bits": "1d00ffff",
+        "difficulty": 1,
+        "chainwork": "0000000000000000000000000000000000000000000000000000000100010001",
+        "nTx": 1,
+        "nextblockhash": "00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048"
+    }"#;
+
+    #[test]
+    fn deserializes_coinbase_inputs_of_verbose_blocks() {
+        let block: BlockInfo<RawTransactionInfo> = serde_json::from_str(GENESIS_BLOCK).unwrap();
+        assert_eq!(block.height, 0);
+        assert_eq!(block.nonce, BlockNonce::Number(2_083_236_893));
+        assert_eq!(block.n_tx, Some(1));
+
+        let tx = &block.tx[0];
+        assert_eq!(tx.txid, block.merkleroot);
+        assert_eq!(tx.vout[0].value, Amount::from_sat(50 * 100_000_000));
+        let input = &tx.vin[0];
+        assert!(input.is_coinbase());
+        assert!(input.coinbase.as_ref().unwrap().starts_with("04ffff001d0104"));
+        assert_eq!(input.txid, None);
+        assert_eq!(input.vout, None);
+        assert!(input.script_sig.is_none());
+        assert_eq!(input.sequence, 0xffff_ffff);
+
+        let transaction = ::Transaction::from_hex(tx.hex.as_ref().unwrap()).unwrap();
+        assert_eq!(transaction.txid(), tx.txid);
     }
 
-    pub fn listreceivedbyaddress(
-        &self,
-        confirmations: u64,
-        include_empty: bool,
-        watch_only: bool,
-    ) -> Result<Vec<ReceivedByAddress>> {
-        let params = json!([confirmations, include_empty, watch_only])
-            .as_array()
-            .cloned()
-            .unwrap();
-        self.request("listreceivedbyaddress", params)
+    #[test]
+    fn deserializes_spending_inputs() {
+        let input: TxIn = serde_json::from_value(json!({
+            "txid": "0437cd7f8525ceed2324359c2d0ba26006d92d856a9c20fa0241106ee5a597c9",
+            "vout": 0,
+            "scriptSig": {
+                "asm": "304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41",
+                "hex": "47304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41"
+            },
+            "sequence": 4294967295u64
+        }))
+        .unwrap();
+        assert!(!input.is_coinbase());
+        assert_eq!(input.vout, Some(0));
+        assert_eq!(input.script_sig.unwrap().hex.len(), 74);
     }
 }
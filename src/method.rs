@@ -0,0 +1,76 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use batch::{Batch, BatchCall, BatchClient};
+use super::{Client, Params, Result, RpcError, Transport};
+
+/// RPC method not wrapped by this crate, described for typed calls.
+///
+/// `Params` must serialize to a JSON array, e.g. a tuple, or to `null` for no params.
+///
+/// ```no_run
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate exonum_bitcoinrpc_zec_exp;
+/// # use exonum_bitcoinrpc_zec_exp::{Client, Result, RpcMethod};
+/// #[derive(Deserialize)]
+/// struct MempoolInfo {
+///     size: u64,
+///     bytes: u64,
+/// }
+///
+/// struct GetMempoolInfo;
+///
+/// impl RpcMethod for GetMempoolInfo {
+///     const NAME: &'static str = "getmempoolinfo";
+///     type Params = ();
+///     type Response = MempoolInfo;
+/// }
+///
+/// # fn main() -> Result<()> {
+/// let client = Client::new("http://127.0.0.1:8232", None, None);
+/// let info = client.call_method::<GetMempoolInfo>(())?;
+/// println!("{} transactions, {} bytes", info.size, info.bytes);
+/// # Ok(())
+/// # }
+/// ```
+pub trait RpcMethod {
+    const NAME: &'static str;
+    type Params: Serialize;
+    type Response: DeserializeOwned;
+}
+
+pub(crate) fn method_params<M: RpcMethod>(params: &M::Params) -> Result<Params> {
+    match serde_json::to_value(params).map_err(RpcError::Json)? {
+        Value::Array(params) => Ok(params),
+        Value::Null => Ok(Vec::new()),
+        param => Ok(vec![param]),
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Calls any RPC method, with the same authentication, logging, retries and
+    /// error mapping as the typed methods.
+    pub fn call<R>(&self, method: &str, params: Params) -> Result<R>
+    where
+        for<'de> R: Deserialize<'de>,
+    {
+        self.request(method, params)
+    }
+
+    pub fn call_method<M: RpcMethod>(&self, params: M::Params) -> Result<M::Response> {
+        self.request(M::NAME, method_params::<M>(&params)?)
+    }
+}
+
+impl<'a, C: BatchClient> Batch<'a, C> {
+    /// Queues a call of `M`; fails if the params do not serialize.
+    pub fn call_method<M>(&mut self, params: M::Params) -> Result<BatchCall<M::Response>>
+    where
+        M: RpcMethod,
+    {
+        let params = method_params::<M>(&params)?;
+        Ok(self.call(M::NAME, params))
+    }
+}
@@ -0,0 +1,441 @@
+//! In-process stand-in for a node, for testing code built on `Client`.
+//!
+//! Register responses per method, then either use `MockNode::client` (or
+//! `MockNode::async_client` with the `async` feature) directly or `MockNode::start` an
+//! HTTP server for code that creates its own `Client`.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use exonum_jsonrpc::{Request, Response};
+#[cfg(feature = "async")]
+use futures::future;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+#[cfg(feature = "async")]
+use super::{AsyncClient, AsyncTransport, RpcFuture};
+use super::{Client, Params, Result, Transport};
+
+type Handler = Arc<dyn Fn(&[Value]) -> ::std::result::Result<Value, MockError> + Send + Sync>;
+
+/// RPC error returned by a mocked method.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl MockError {
+    pub fn new<S: Into<String>>(code: i64, message: S) -> MockError {
+        MockError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    handlers: Mutex<HashMap<String, Handler>>,
+    calls: Mutex<Vec<Request>>,
+}
+
+impl State {
+    fn handle(&self, request: &Request) -> Response {
+        self.calls.lock().unwrap().push(request.clone());
+        // Released before calling the handler, which may register handlers itself.
+        let handler = self.handlers.lock().unwrap().get(&request.method).cloned();
+        let result = match handler {
+            Some(handler) => handler(&request.params),
+            None => Err(MockError::new(-32601, "Method not found")),
+        };
+        match result {
+            Ok(result) => Response {
+                result: Some(result),
+                error: None,
+                id: request.id.clone(),
+            },
+            Err(e) => Response {
+                result: None,
+                error: Some(json!({ "code": e.code, "message": e.message })),
+                id: request.id.clone(),
+            },
+        }
+    }
+}
+
+/// Mock node answering registered methods; unknown methods fail with -32601.
+///
+/// Clones share responses and recorded calls.
+#[derive(Clone, Default)]
+pub struct MockNode {
+    state: Arc<State>,
+}
+
+impl MockNode {
+    pub fn new() -> MockNode {
+        MockNode::default()
+    }
+
+    /// Answers every call of `method` with `result`.
+    pub fn respond(&self, method: &str, result: Value) -> &MockNode {
+        self.handle(method, move |_| Ok(result.clone()))
+    }
+
+    /// Fails every call of `method` with the RPC error `code`.
+    pub fn fail<S: Into<String>>(&self, method: &str, code: i64, message: S) -> &MockNode {
+        let error = MockError::new(code, message);
+        self.handle(method, move |_| Err(error.clone()))
+    }
+
+    /// Answers calls of `method` by calling `handler` with the params.
+    pub fn handle<F>(&self, method: &str, handler: F) -> &MockNode
+    where
+        F: Fn(&[Value]) -> ::std::result::Result<Value, MockError> + Send + Sync + 'static,
+    {
+        self.state
+            .handlers
+            .lock()
+            .unwrap()
+            .insert(method.to_owned(), Arc::new(handler));
+        self
+    }
+
+    /// Returns all received requests in order.
+    pub fn calls(&self) -> Vec<Request> {
+        self.state.calls.lock().unwrap().clone()
+    }
+
+    /// Returns the params of each received call of `method`.
+    pub fn calls_to(&self, method: &str) -> Vec<Params> {
+        self.state
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.method == method)
+            .map(|request| request.params.clone())
+            .collect()
+    }
+
+    pub fn clear_calls(&self) {
+        self.state.calls.lock().unwrap().clear();
+    }
+
+    /// Creates a client talking to this node without going through HTTP.
+    pub fn client(&self) -> Client<MockNode> {
+        Client::with_transport(self.clone())
+    }
+
+    /// Creates an `AsyncClient` talking to this node without going through HTTP.
+    #[cfg(feature = "async")]
+    pub fn async_client(&self) -> AsyncClient<MockNode> {
+        AsyncClient::with_transport(self.clone())
+    }
+
+    /// Serves the node over HTTP on a free local port until the server is dropped.
+    pub fn start(&self) -> io::Result<MockServer> {
+        let state = Arc::clone(&self.state);
+        MockServer::start(move |request| {
+            let body = match serde_json::from_slice::<Value>(&request.body)? {
+                Value::Array(requests) => {
+                    let responses = requests
+                        .into_iter()
+                        .map(|request| Ok(state.handle(&serde_json::from_value(request)?)))
+                        .collect::<::std::result::Result<Vec<_>, serde_json::Error>>()?;
+                    serde_json::to_vec(&responses)?
+                }
+                request => serde_json::to_vec(&state.handle(&serde_json::from_value(request)?))?,
+            };
+            Ok((200, body))
+        })
+    }
+}
+
+impl Transport for MockNode {
+    fn send_request(&self, request: &Request) -> Result<Response> {
+        Ok(self.state.handle(request))
+    }
+
+    fn send_batch(&self, requests: &[Request]) -> Result<Vec<Response>> {
+        Ok(requests
+            .iter()
+            .map(|request| self.state.handle(request))
+            .collect())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for MockNode {
+    fn send_request(&self, request: &Request) -> RpcFuture<Response> {
+        Box::new(future::ok(self.state.handle(request)))
+    }
+
+    fn send_batch(&self, requests: &[Request]) -> RpcFuture<Vec<Response>> {
+        Box::new(future::result(Transport::send_batch(self, requests)))
+    }
+}
+
+/// Running HTTP endpoint of a `MockNode`.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+}
+
+// Request as seen by the HTTP server.
+pub(crate) struct HttpRequest {
+    headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl MockServer {
+    // Serves `answer`, returning the status and body of each response, on a free port.
+    pub(crate) fn start<F>(answer: F) -> io::Result<MockServer>
+    where
+        F: Fn(&HttpRequest) -> io::Result<(u16, Vec<u8>)> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let answer = Arc::new(answer);
+        let stop = Arc::clone(&stopped);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let answer = Arc::clone(&answer);
+                thread::spawn(move || {
+                    if let Err(e) = serve(stream, &*answer) {
+                        debug!("Mock node connection failed: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(MockServer { addr, stopped })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes up the accept loop so it notices the flag.
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+// Answers HTTP requests on a keep-alive connection until the client closes it.
+fn serve<F>(stream: TcpStream, answer: &F) -> io::Result<()>
+where
+    F: Fn(&HttpRequest) -> io::Result<(u16, Vec<u8>)>,
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            let mut parts = header.splitn(2, ':');
+            let name = parts.next().unwrap_or("").to_owned();
+            let value = parts.next().unwrap_or("").trim().to_owned();
+            headers.push((name, value));
+        }
+        let mut request = HttpRequest {
+            headers,
+            body: Vec::new(),
+        };
+        let length = request
+            .header("content-length")
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length"))?;
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+
+        let (status, body) = answer(&request)?;
+        let reason = StatusCode::from_u16(status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("");
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            status,
+            reason,
+            body.len()
+        )?;
+        writer.write_all(&body)?;
+        writer.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    use serde_json::{self, Value};
+
+    use super::MockNode;
+    use {Client, Error, RpcError};
+
+    const RPC_ERROR_CODES: &[i64] = &[
+        -32601, -32600, -32602, -32603, -32700, -1, -2, -3, -4, -5, -6, -7, -8, -9, -10, -11, -12,
+        -13, -14, -15, -16, -17, -18, -19, -20, -22, -23, -24, -25, -26, -27, -28, -29, -30, -31,
+        -32,
+    ];
+
+    #[test]
+    fn maps_rpc_error_codes() {
+        let node = MockNode::new();
+        let client = node.client();
+        let mut displays = HashSet::new();
+        for &code in RPC_ERROR_CODES {
+            node.fail("getblockcount", code, "boom");
+            let error = client.getblockcount().unwrap_err();
+            assert_eq!(error.code(), Some(code));
+            assert_eq!(error.message(), Some("boom"));
+            match error {
+                Error::Rpc(_) => panic!("{} is not mapped", code),
+                _ => assert!(displays.insert(error.to_string()), "{}: {}", code, error),
+            }
+        }
+
+        node.fail("getblockcount", -9, "");
+        assert!(matches!(client.getblockcount(), Err(Error::ClientNotConnected(_))));
+        node.fail("getblockcount", -29, "");
+        assert!(matches!(client.getblockcount(), Err(Error::NodeNotConnected(_))));
+
+        node.fail("getblockcount", -99, "unknown");
+        let error = client.getblockcount().unwrap_err();
+        assert!(matches!(error, Error::Rpc(RpcError::Rpc(_))));
+        assert_eq!(error.code(), Some(-99));
+        assert_eq!(error.message(), Some("unknown"));
+    }
+
+    #[test]
+    fn unregistered_methods_are_not_found() {
+        let client = MockNode::new().client();
+        assert!(matches!(client.getblockcount(), Err(Error::MethodNotFound(_))));
+    }
+
+    #[test]
+    fn handlers_may_register_handlers() {
+        let node = MockNode::new();
+        let inner = node.clone();
+        node.handle("getblockcount", move |_| {
+            inner.respond("getblockcount", json!(2));
+            Ok(json!(1))
+        });
+        let client = node.client();
+        assert_eq!(client.getblockcount().unwrap(), 1);
+        assert_eq!(client.getblockcount().unwrap(), 2);
+        assert_eq!(node.calls_to("getblockcount").len(), 2);
+    }
+
+    #[test]
+    fn serves_clients_over_http() {
+        let node = MockNode::new();
+        node.respond("getblockcount", json!(7));
+        node.handle("getblockhash", |params| Ok(json!(format!("hash{}", params[0]))));
+        node.fail("getblock", -5, "Block not found");
+        let server = node.start().unwrap();
+        let client = Client::new(server.url(), Some("user".into()), Some("password".into()));
+
+        assert_eq!(client.getblockcount().unwrap(), 7);
+        assert!(matches!(client.getblock("00"), Err(Error::NoInformation(_))));
+
+        let mut batch = client.batch();
+        let first = batch.getblockhash(1);
+        let count = batch.call::<u64>("getblockcount", vec![]);
+        let second = batch.getblockhash(2);
+        let mut responses = batch.send().unwrap();
+        assert_eq!(responses.take(second).unwrap(), "hash2");
+        assert_eq!(responses.take(count).unwrap(), 7);
+        assert_eq!(responses.take(first).unwrap(), "hash1");
+        assert_eq!(node.calls_to("getblockhash"), vec![vec![json!(1)], vec![json!(2)]]);
+    }
+
+    #[test]
+    fn reads_bodies_split_across_reads() {
+        let node = MockNode::new();
+        node.respond("getblockcount", json!(7));
+        let server = node.start().unwrap();
+
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        for id in 1..3 {
+            let body = format!(r#"{{"method":"getblockcount","params":[],"id":{}}}"#, id);
+            write!(
+                stream,
+                "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            ).unwrap();
+            let (head, tail) = body.as_bytes().split_at(body.len() / 2);
+            stream.write_all(head).unwrap();
+            stream.flush().unwrap();
+            thread::sleep(Duration::from_millis(50));
+            stream.write_all(tail).unwrap();
+
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line, "HTTP/1.1 200 OK\r\n");
+            let mut length = 0;
+            loop {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if line.to_lowercase().starts_with("content-length:") {
+                    length = line[15..].trim().parse().unwrap();
+                }
+            }
+            let mut response = vec![0; length];
+            reader.read_exact(&mut response).unwrap();
+            let response: Value = serde_json::from_slice(&response).unwrap();
+            assert_eq!(response["result"], json!(7));
+            assert_eq!(response["id"], json!(id));
+        }
+        assert_eq!(node.calls_to("getblockcount").len(), 2);
+    }
+}
@@ -0,0 +1,404 @@
+use std::fmt;
+
+use amount::Amount;
+
+/// Number of confirmations before coinbase outputs may be spent, on both coins.
+pub const COINBASE_MATURITY: u32 = 100;
+
+/// Default fee of zcashd's `z_*` operations before ZIP 317 (zcashd 5.5) made it depend on
+/// the number of inputs and outputs.
+pub const ZCASH_DEFAULT_FEE: Amount = Amount::from_sat(10_000);
+
+/// Chain the node is running on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Network {
+    ZcashMainnet,
+    ZcashTestnet,
+    ZcashRegtest,
+    BitcoinMainnet,
+    BitcoinTestnet,
+    BitcoinRegtest,
+}
+
+impl Network {
+    pub fn is_zcash(self) -> bool {
+        matches!(
+            self,
+            Network::ZcashMainnet | Network::ZcashTestnet | Network::ZcashRegtest
+        )
+    }
+
+    pub fn is_mainnet(self) -> bool {
+        matches!(self, Network::ZcashMainnet | Network::BitcoinMainnet)
+    }
+
+    pub fn is_regtest(self) -> bool {
+        matches!(self, Network::ZcashRegtest | Network::BitcoinRegtest)
+    }
+
+    /// Returns the mainnet of the same coin.
+    pub fn mainnet(self) -> Network {
+        if self.is_zcash() {
+            Network::ZcashMainnet
+        } else {
+            Network::BitcoinMainnet
+        }
+    }
+
+    /// Returns the testnet of the same coin.
+    pub fn testnet(self) -> Network {
+        if self.is_zcash() {
+            Network::ZcashTestnet
+        } else {
+            Network::BitcoinTestnet
+        }
+    }
+
+    /// Returns the regtest network of the same coin.
+    pub fn regtest(self) -> Network {
+        if self.is_zcash() {
+            Network::ZcashRegtest
+        } else {
+            Network::BitcoinRegtest
+        }
+    }
+
+    pub fn rpc_port(self) -> u16 {
+        match self {
+            Network::ZcashMainnet => 8232,
+            Network::ZcashTestnet | Network::ZcashRegtest => 18232,
+            Network::BitcoinMainnet => 8332,
+            Network::BitcoinTestnet => 18332,
+            Network::BitcoinRegtest => 18443,
+        }
+    }
+
+    /// Subdirectory of the data directory holding this network's files, e.g. the `.cookie`.
+    pub fn datadir_subdir(self) -> Option<&'static str> {
+        match self {
+            Network::ZcashMainnet | Network::BitcoinMainnet => None,
+            Network::ZcashTestnet | Network::BitcoinTestnet => Some("testnet3"),
+            Network::ZcashRegtest | Network::BitcoinRegtest => Some("regtest"),
+        }
+    }
+
+    /// Two byte base58check prefix on Zcash, a single byte on Bitcoin.
+    pub fn p2pkh_prefix(self) -> &'static [u8] {
+        match self {
+            Network::ZcashMainnet => &[0x1c, 0xb8],
+            Network::ZcashTestnet | Network::ZcashRegtest => &[0x1d, 0x25],
+            Network::BitcoinMainnet => &[0x00],
+            Network::BitcoinTestnet | Network::BitcoinRegtest => &[0x6f],
+        }
+    }
+
+    pub fn p2sh_prefix(self) -> &'static [u8] {
+        match self {
+            Network::ZcashMainnet => &[0x1c, 0xbd],
+            Network::ZcashTestnet | Network::ZcashRegtest => &[0x1c, 0xba],
+            Network::BitcoinMainnet => &[0x05],
+            Network::BitcoinTestnet | Network::BitcoinRegtest => &[0xc4],
+        }
+    }
+
+    /// Human readable part of segwit addresses, Bitcoin only.
+    pub fn segwit_hrp(self) -> Option<&'static str> {
+        match self {
+            Network::BitcoinMainnet => Some("bc"),
+            Network::BitcoinTestnet => Some("tb"),
+            Network::BitcoinRegtest => Some("bcrt"),
+            _ => None,
+        }
+    }
+
+    /// Human readable part of Sapling payment addresses, Zcash only.
+    pub fn sapling_hrp(self) -> Option<&'static str> {
+        match self {
+            Network::ZcashMainnet => Some("zs"),
+            Network::ZcashTestnet => Some("ztestsapling"),
+            Network::ZcashRegtest => Some("zregtestsapling"),
+            _ => None,
+        }
+    }
+
+    /// Human readable part of Unified Addresses, Zcash only.
+    pub fn unified_hrp(self) -> Option<&'static str> {
+        match self {
+            Network::ZcashMainnet => Some("u"),
+            Network::ZcashTestnet => Some("utest"),
+            Network::ZcashRegtest => Some("uregtest"),
+            _ => None,
+        }
+    }
+
+    pub fn coinbase_maturity(self) -> u32 {
+        COINBASE_MATURITY
+    }
+
+    pub fn max_money(self) -> Amount {
+        Amount::MAX_MONEY
+    }
+
+    /// Same as `Amount::is_valid_money`; both coins share the 21 million cap.
+    pub fn is_valid_amount(self, amount: Amount) -> bool {
+        amount.is_valid_money()
+    }
+
+    /// Height at which `upgrade` activated.
+    ///
+    /// Returns `None` for upgrades of the other coin and on regtest, where
+    /// activation heights are set by the node's command line.
+    pub fn activation_height(self, upgrade: NetworkUpgrade) -> Option<u64> {
+        use self::NetworkUpgrade::*;
+
+        let height = match (self, upgrade) {
+            (Network::ZcashMainnet, Overwinter) => 347_500,
+            (Network::ZcashMainnet, Sapling) => 419_200,
+            (Network::ZcashMainnet, Blossom) => 653_600,
+            (Network::ZcashMainnet, Heartwood) => 903_000,
+            (Network::ZcashMainnet, Canopy) => 1_046_400,
+            (Network::ZcashMainnet, Nu5) => 1_687_104,
+            (Network::ZcashMainnet, Nu6) => 2_726_400,
+            (Network::ZcashTestnet, Overwinter) => 207_500,
+            (Network::ZcashTestnet, Sapling) => 280_000,
+            (Network::ZcashTestnet, Blossom) => 584_000,
+            (Network::ZcashTestnet, Heartwood) => 903_800,
+            (Network::ZcashTestnet, Canopy) => 1_028_500,
+            (Network::ZcashTestnet, Nu5) => 1_842_420,
+            (Network::ZcashTestnet, Nu6) => 2_976_000,
+            (Network::BitcoinMainnet, Bip34) => 227_931,
+            (Network::BitcoinMainnet, Bip66) => 363_725,
+            (Network::BitcoinMainnet, Bip65) => 388_381,
+            (Network::BitcoinMainnet, Csv) => 419_328,
+            (Network::BitcoinMainnet, Segwit) => 481_824,
+            (Network::BitcoinTestnet, Bip34) => 21_111,
+            (Network::BitcoinTestnet, Bip66) => 330_776,
+            (Network::BitcoinTestnet, Bip65) => 581_885,
+            (Network::BitcoinTestnet, Csv) => 770_112,
+            (Network::BitcoinTestnet, Segwit) => 834_624,
+            _ => return None,
+        };
+        Some(height)
+    }
+
+    /// Latest network upgrade active at `height`, Zcash only.
+    pub fn upgrade_at(self, height: u64) -> Option<NetworkUpgrade> {
+        NetworkUpgrade::ZCASH
+            .iter()
+            .rev()
+            .find(|&&upgrade| {
+                self.activation_height(upgrade)
+                    .is_some_and(|activation| activation <= height)
+            }).cloned()
+    }
+
+    // Maps the `chain` field of `getblockchaininfo`.
+    pub(crate) fn from_chain_name(chain: &str, zcash: bool) -> Option<Network> {
+        let network = match chain {
+            "main" => Network::BitcoinMainnet,
+            "test" => Network::BitcoinTestnet,
+            "regtest" => Network::BitcoinRegtest,
+            _ => return None,
+        };
+        Some(if zcash {
+            match network {
+                Network::BitcoinMainnet => Network::ZcashMainnet,
+                Network::BitcoinTestnet => Network::ZcashTestnet,
+                _ => Network::ZcashRegtest,
+            }
+        } else {
+            network
+        })
+    }
+
+    // Section name used by bitcoind for network specific settings in `bitcoin.conf`.
+    pub(crate) fn config_section(self) -> &'static str {
+        match self {
+            Network::ZcashMainnet | Network::BitcoinMainnet => "main",
+            Network::ZcashTestnet | Network::BitcoinTestnet => "test",
+            Network::ZcashRegtest | Network::BitcoinRegtest => "regtest",
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Network::ZcashMainnet => "zcash-mainnet",
+            Network::ZcashTestnet => "zcash-testnet",
+            Network::ZcashRegtest => "zcash-regtest",
+            Network::BitcoinMainnet => "bitcoin-mainnet",
+            Network::BitcoinTestnet => "bitcoin-testnet",
+            Network::BitcoinRegtest => "bitcoin-regtest",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Consensus rule changes with fixed activation heights.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NetworkUpgrade {
+    Overwinter,
+    Sapling,
+    Blossom,
+    Heartwood,
+    Canopy,
+    Nu5,
+    Nu6,
+    Bip34,
+    Bip66,
+    Bip65,
+    Csv,
+    Segwit,
+}
+
+impl NetworkUpgrade {
+    /// Zcash network upgrades in activation order.
+    pub const ZCASH: [NetworkUpgrade; 7] = [
+        NetworkUpgrade::Overwinter,
+        NetworkUpgrade::Sapling,
+        NetworkUpgrade::Blossom,
+        NetworkUpgrade::Heartwood,
+        NetworkUpgrade::Canopy,
+        NetworkUpgrade::Nu5,
+        NetworkUpgrade::Nu6,
+    ];
+
+    /// Consensus branch id of a Zcash network upgrade.
+    pub fn branch_id(self) -> Option<u32> {
+        let id = match self {
+            NetworkUpgrade::Overwinter => 0x5ba8_1b19,
+            NetworkUpgrade::Sapling => 0x76b8_09bb,
+            NetworkUpgrade::Blossom => 0x2bb4_0e60,
+            NetworkUpgrade::Heartwood => 0xf5b9_230b,
+            NetworkUpgrade::Canopy => 0xe9ff_75a6,
+            NetworkUpgrade::Nu5 => 0xc2d6_d0b4,
+            NetworkUpgrade::Nu6 => 0xc8e7_1055,
+            _ => return None,
+        };
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Network, NetworkUpgrade};
+    use Amount;
+
+    const NETWORKS: [Network; 6] = [
+        Network::ZcashMainnet,
+        Network::ZcashTestnet,
+        Network::ZcashRegtest,
+        Network::BitcoinMainnet,
+        Network::BitcoinTestnet,
+        Network::BitcoinRegtest,
+    ];
+
+    #[test]
+    fn chain_parameters() {
+        let ports = [8232, 18232, 18232, 8332, 18332, 18443];
+        // (p2pkh prefix, p2sh prefix)
+        let prefixes: [(&[u8], &[u8]); 6] = [
+            (&[0x1c, 0xb8], &[0x1c, 0xbd]),
+            (&[0x1d, 0x25], &[0x1c, 0xba]),
+            (&[0x1d, 0x25], &[0x1c, 0xba]),
+            (&[0x00], &[0x05]),
+            (&[0x6f], &[0xc4]),
+            (&[0x6f], &[0xc4]),
+        ];
+        // (segwit, sapling and unified hrp)
+        let hrps = [
+            (None, Some("zs"), Some("u")),
+            (None, Some("ztestsapling"), Some("utest")),
+            (None, Some("zregtestsapling"), Some("uregtest")),
+            (Some("bc"), None, None),
+            (Some("tb"), None, None),
+            (Some("bcrt"), None, None),
+        ];
+        for (i, &network) in NETWORKS.iter().enumerate() {
+            assert_eq!(network.rpc_port(), ports[i], "{}", network);
+            assert_eq!((network.p2pkh_prefix(), network.p2sh_prefix()), prefixes[i], "{}", network);
+            let network_hrps = (network.segwit_hrp(), network.sapling_hrp(), network.unified_hrp());
+            assert_eq!(network_hrps, hrps[i], "{}", network);
+        }
+    }
+
+    #[test]
+    fn zcash_upgrades() {
+        use super::NetworkUpgrade::*;
+
+        // (upgrade, branch id, mainnet and testnet activation heights)
+        let expected = [
+            (Overwinter, 0x5ba8_1b19, 347_500, 207_500),
+            (Sapling, 0x76b8_09bb, 419_200, 280_000),
+            (Blossom, 0x2bb4_0e60, 653_600, 584_000),
+            (Heartwood, 0xf5b9_230b, 903_000, 903_800),
+            (Canopy, 0xe9ff_75a6, 1_046_400, 1_028_500),
+            (Nu5, 0xc2d6_d0b4, 1_687_104, 1_842_420),
+            (Nu6, 0xc8e7_1055, 2_726_400, 2_976_000),
+        ];
+        assert_eq!(NetworkUpgrade::ZCASH.len(), expected.len());
+        for (&upgrade, &(expected_upgrade, branch_id, mainnet, testnet)) in
+            NetworkUpgrade::ZCASH.iter().zip(&expected)
+        {
+            assert_eq!(upgrade, expected_upgrade);
+            assert_eq!(upgrade.branch_id(), Some(branch_id));
+            assert_eq!(Network::ZcashMainnet.activation_height(upgrade), Some(mainnet));
+            assert_eq!(Network::ZcashTestnet.activation_height(upgrade), Some(testnet));
+            assert_eq!(Network::ZcashRegtest.activation_height(upgrade), None);
+            assert_eq!(Network::BitcoinMainnet.activation_height(upgrade), None);
+        }
+
+        assert_eq!(Network::ZcashMainnet.upgrade_at(347_499), None);
+        assert_eq!(Network::ZcashMainnet.upgrade_at(347_500), Some(Overwinter));
+        assert_eq!(Network::ZcashMainnet.upgrade_at(1_687_103), Some(Canopy));
+        assert_eq!(Network::ZcashTestnet.upgrade_at(3_000_000), Some(Nu6));
+        assert_eq!(Network::BitcoinMainnet.upgrade_at(3_000_000), None);
+    }
+
+    #[test]
+    fn bitcoin_upgrades() {
+        use super::NetworkUpgrade::*;
+
+        // (upgrade, mainnet and testnet activation heights)
+        let expected = [
+            (Bip34, 227_931, 21_111),
+            (Bip66, 363_725, 330_776),
+            (Bip65, 388_381, 581_885),
+            (Csv, 419_328, 770_112),
+            (Segwit, 481_824, 834_624),
+        ];
+        for &(upgrade, mainnet, testnet) in &expected {
+            assert_eq!(upgrade.branch_id(), None);
+            assert_eq!(Network::BitcoinMainnet.activation_height(upgrade), Some(mainnet));
+            assert_eq!(Network::BitcoinTestnet.activation_height(upgrade), Some(testnet));
+            assert_eq!(Network::BitcoinRegtest.activation_height(upgrade), None);
+            assert_eq!(Network::ZcashMainnet.activation_height(upgrade), None);
+        }
+    }
+
+    #[test]
+    fn chain_names_and_config_sections() {
+        let names = ["main", "test", "regtest"];
+        for (i, &network) in NETWORKS.iter().enumerate() {
+            let name = names[i % 3];
+            assert_eq!(Network::from_chain_name(name, network.is_zcash()), Some(network));
+            assert_eq!(network.config_section(), name);
+            assert_eq!(network.mainnet(), NETWORKS[i - i % 3]);
+            assert_eq!(network.testnet(), NETWORKS[i - i % 3 + 1]);
+            assert_eq!(network.regtest(), NETWORKS[i - i % 3 + 2]);
+        }
+        assert_eq!(Network::from_chain_name("signet", false), None);
+        assert_eq!(Network::from_chain_name("", true), None);
+    }
+
+    #[test]
+    fn valid_amounts() {
+        for &network in &NETWORKS {
+            assert!(network.is_valid_amount(Amount::ZERO));
+            assert!(network.is_valid_amount(Amount::MAX_MONEY));
+            assert!(!network.is_valid_amount(Amount::from_sat(-1)));
+            assert!(!network.is_valid_amount(Amount::MAX_MONEY + Amount::from_sat(1)));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Params of the methods with optional arguments, shared by `Client` and `AsyncClient`.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::value::Value;
+
+use super::{Amount, OperationState, Params, ReceiverType, Rescan, SecretString,
+            SendManyRecipient, TransactionInput, TransactionOutput, ZCASH_DEFAULT_FEE};
+
+const DEFAULT_RECEIVER_TYPES: [ReceiverType; 3] = [
+    ReceiverType::P2pkh,
+    ReceiverType::Sapling,
+    ReceiverType::Orchard,
+];
+
+// Appends optional arguments up to the last given one; the ones before it are sent
+// with their defaults, since JSON-RPC params are positional.
+fn push_optional(params: &mut Params, optional: Vec<(Value, bool)>) {
+    if let Some(last) = optional.iter().rposition(|&(_, given)| given) {
+        params.extend(optional.into_iter().take(last + 1).map(|(value, _)| value));
+    }
+}
+
+pub(crate) fn z_getaddressforaccount(
+    account: u32,
+    receiver_types: Option<&[ReceiverType]>,
+    diversifier_index: Option<u64>,
+) -> Params {
+    let mut params = vec![json!(account)];
+    push_optional(
+        &mut params,
+        vec![
+            (
+                json!(receiver_types.unwrap_or(&DEFAULT_RECEIVER_TYPES)),
+                receiver_types.is_some(),
+            ),
+            (json!(diversifier_index), diversifier_index.is_some()),
+        ],
+    );
+    params
+}
+
+pub(crate) fn z_getbalanceforaccount(account: u32, minconf: Option<u32>) -> Params {
+    let mut params = vec![json!(account)];
+    push_optional(&mut params, vec![(json!(minconf), minconf.is_some())]);
+    params
+}
+
+pub(crate) fn z_sendmany(
+    from: &str,
+    recipients: &[SendManyRecipient],
+    minconf: u32,
+    fee: Option<Amount>,
+) -> Params {
+    let mut params = json!([from, recipients, minconf])
+        .as_array()
+        .cloned()
+        .unwrap();
+    push_optional(&mut params, vec![(json!(fee), fee.is_some())]);
+    params
+}
+
+pub(crate) fn z_listoperationids(state: Option<OperationState>) -> Params {
+    let mut params = Vec::new();
+    push_optional(&mut params, vec![(json!(state), state.is_some())]);
+    params
+}
+
+pub(crate) fn createrawtransaction(
+    transactions: &[TransactionInput],
+    outputs: &[TransactionOutput],
+    data: Option<String>,
+) -> Params {
+    let mut map = BTreeMap::new();
+    map.extend(outputs.iter().map(|x| (x.address.clone(), json!(x.value))));
+    if let Some(data) = data {
+        map.insert("data".into(), Value::String(data));
+    }
+    json!([transactions, map]).as_array().cloned().unwrap()
+}
+
+// Params of `z_importkey` and `z_importviewingkey`.
+pub(crate) fn import_key(key: &SecretString, rescan: Rescan, start_height: Option<u64>) -> Params {
+    let mut params = json!([key, rescan]).as_array().cloned().unwrap();
+    push_optional(&mut params, vec![(json!(start_height), start_height.is_some())]);
+    params
+}
+
+// A missing fee is sent as `ZCASH_DEFAULT_FEE` when a limit follows, since zcashd
+// before 5.5 rejects a null fee.
+pub(crate) fn z_shieldcoinbase(
+    from: &str,
+    to: &str,
+    fee: Option<Amount>,
+    limit: Option<u32>,
+) -> Params {
+    let mut params = json!([from, to]).as_array().cloned().unwrap();
+    push_optional(
+        &mut params,
+        vec![
+            (json!(fee.unwrap_or(ZCASH_DEFAULT_FEE)), fee.is_some()),
+            (json!(limit.unwrap_or(50)), limit.is_some()),
+        ],
+    );
+    params
+}
+
+pub(crate) fn z_mergetoaddress<V: AsRef<str> + Serialize>(
+    from_addresses: &[V],
+    to: &str,
+    fee: Option<Amount>,
+    utxo_limit: Option<u32>,
+    note_limit: Option<u32>,
+    memo: Option<&str>,
+) -> Params {
+    let mut params = json!([from_addresses, to]).as_array().cloned().unwrap();
+    push_optional(
+        &mut params,
+        vec![
+            (json!(fee.unwrap_or(ZCASH_DEFAULT_FEE)), fee.is_some()),
+            (json!(utxo_limit.unwrap_or(50)), utxo_limit.is_some()),
+            (json!(note_limit.unwrap_or(20)), note_limit.is_some()),
+            (json!(memo), memo.is_some()),
+        ],
+    );
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{z_getaddressforaccount, z_mergetoaddress, z_shieldcoinbase};
+    use {Amount, ReceiverType};
+
+    #[test]
+    fn sends_defaults_before_given_arguments() {
+        assert_eq!(z_shieldcoinbase("*", "zs", None, None), vec![json!("*"), json!("zs")]);
+        assert_eq!(
+            z_shieldcoinbase("*", "zs", None, Some(10)),
+            vec![json!("*"), json!("zs"), json!(0.0001), json!(10)]
+        );
+        assert_eq!(
+            z_shieldcoinbase("*", "zs", Some(Amount::from_sat(5000)), None),
+            vec![json!("*"), json!("zs"), json!(0.00005)]
+        );
+
+        let from = ["t1"];
+        assert_eq!(
+            z_mergetoaddress(&from, "zs", None, None, None, Some("f0")),
+            vec![
+                json!(["t1"]),
+                json!("zs"),
+                json!(0.0001),
+                json!(50),
+                json!(20),
+                json!("f0"),
+            ]
+        );
+        assert_eq!(
+            z_mergetoaddress(&from, "zs", None, Some(5), None, None),
+            vec![json!(["t1"]), json!("zs"), json!(0.0001), json!(5)]
+        );
+
+        assert_eq!(z_getaddressforaccount(1, None, None), vec![json!(1)]);
+        assert_eq!(
+            z_getaddressforaccount(1, Some(&[ReceiverType::Orchard]), None),
+            vec![json!(1), json!(["orchard"])]
+        );
+        assert_eq!(
+            z_getaddressforaccount(1, None, Some(7)),
+            vec![json!(1), json!(["p2pkh", "sapling", "orchard"]), json!(7)]
+        );
+    }
+}
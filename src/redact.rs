@@ -0,0 +1,241 @@
+//! Masking of secrets in logged requests and responses.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+const MASK: &str = "***";
+
+/// Parts of a call hidden from trace logs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Redaction {
+    params: Vec<usize>,
+    all_params: bool,
+    result: bool,
+}
+
+impl Redaction {
+    /// Masks the parameters at the given positions.
+    pub fn params(indices: &[usize]) -> Redaction {
+        Redaction {
+            params: indices.to_vec(),
+            ..Redaction::default()
+        }
+    }
+
+    pub fn all_params() -> Redaction {
+        Redaction {
+            all_params: true,
+            ..Redaction::default()
+        }
+    }
+
+    pub fn result() -> Redaction {
+        Redaction {
+            result: true,
+            ..Redaction::default()
+        }
+    }
+
+    /// Additionally masks the result.
+    pub fn and_result(mut self) -> Redaction {
+        self.result = true;
+        self
+    }
+
+    fn apply_params(&self, params: &mut [Value]) {
+        for (index, param) in params.iter_mut().enumerate() {
+            if self.all_params || self.params.contains(&index) {
+                *param = Value::String(MASK.to_owned());
+            }
+        }
+    }
+}
+
+// Redactions per method name, seeded with the wallet RPCs carrying keys or passphrases.
+#[derive(Clone, Debug)]
+pub(crate) struct Redactor {
+    methods: HashMap<String, Redaction>,
+}
+
+impl Default for Redactor {
+    fn default() -> Redactor {
+        let defaults = [
+            ("dumpprivkey", Redaction::result()),
+            ("importprivkey", Redaction::params(&[0])),
+            ("signrawtransaction", Redaction::params(&[2])),
+            ("signrawtransactionwithkey", Redaction::params(&[1])),
+            ("walletpassphrase", Redaction::params(&[0])),
+            ("walletpassphrasechange", Redaction::params(&[0, 1])),
+            ("encryptwallet", Redaction::params(&[0])),
+            ("z_exportkey", Redaction::result()),
+            ("z_importkey", Redaction::params(&[0])),
+            ("z_exportviewingkey", Redaction::result()),
+            ("z_importviewingkey", Redaction::params(&[0])),
+        ];
+        Redactor {
+            methods: defaults
+                .iter()
+                .map(|(method, redaction)| (method.to_string(), redaction.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Redactor {
+    pub(crate) fn insert(&mut self, method: String, redaction: Redaction) {
+        self.methods.insert(method, redaction);
+    }
+
+    pub(crate) fn params(&self, method: &str, params: &[Value]) -> Vec<Value> {
+        let mut params = params.to_vec();
+        if let Some(redaction) = self.methods.get(method) {
+            redaction.apply_params(&mut params);
+        }
+        params
+    }
+
+    pub(crate) fn result(&self, method: &str, result: Value) -> Value {
+        match self.methods.get(method) {
+            Some(redaction) if redaction.result && !result.is_null() => {
+                Value::String(MASK.to_owned())
+            }
+            _ => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Mutex, Once};
+    use std::time::Duration;
+
+    use log::{self, Log, Metadata, Record};
+    use serde_json::Value;
+
+    use super::{Redaction, Redactor};
+    use mock::MockNode;
+    use {DependentOutput, SecretString};
+
+    // Collects trace logs of all tests; tests look for their own secrets only.
+    struct Capture;
+
+    static LOGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    static CAPTURE: Capture = Capture;
+    static INSTALL: Once = Once::new();
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            LOGS.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture_logs() {
+        INSTALL.call_once(|| {
+            log::set_logger(&CAPTURE).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+    }
+
+    fn logs_of(method: &str) -> Vec<String> {
+        let method = format!("method: {:?}", method);
+        LOGS.lock()
+            .unwrap()
+            .iter()
+            .filter(|line| line.contains(&method))
+            .cloned()
+            .collect()
+    }
+
+    fn params(values: &[&str]) -> Vec<Value> {
+        values.iter().map(|value| json!(value)).collect()
+    }
+
+    #[test]
+    fn masks_default_methods() {
+        let redactor = Redactor::default();
+        assert_eq!(
+            redactor.params("walletpassphrase", &[json!("hunter2"), json!(60)]),
+            vec![json!("***"), json!(60)]
+        );
+        assert_eq!(
+            redactor.params("signrawtransaction", &params(&["tx", "outputs", "keys", "ALL"])),
+            params(&["tx", "outputs", "***", "ALL"])
+        );
+        assert_eq!(
+            redactor.params("walletpassphrasechange", &params(&["old", "new"])),
+            params(&["***", "***"])
+        );
+        for method in &["dumpprivkey", "z_exportkey", "z_exportviewingkey"] {
+            assert_eq!(redactor.result(method, json!("secret")), json!("***"));
+            assert_eq!(redactor.params(method, &params(&["addr"])), params(&["addr"]));
+        }
+        assert_eq!(redactor.params("getblockhash", &[json!(1)]), vec![json!(1)]);
+        assert_eq!(redactor.result("getblockhash", json!("00ff")), json!("00ff"));
+    }
+
+    #[test]
+    fn applies_redactions() {
+        let mut redactor = Redactor::default();
+        redactor.insert("positions".into(), Redaction::params(&[1, 5]));
+        redactor.insert("everything".into(), Redaction::all_params().and_result());
+        redactor.insert("dumpprivkey".into(), Redaction::params(&[0]));
+
+        // Positions past the end are ignored.
+        assert_eq!(redactor.params("positions", &params(&["a", "b"])), params(&["a", "***"]));
+        assert_eq!(redactor.params("positions", &[]), Vec::<Value>::new());
+        assert_eq!(redactor.result("positions", json!("result")), json!("result"));
+
+        assert_eq!(redactor.params("everything", &params(&["a", "b"])), params(&["***", "***"]));
+        assert_eq!(redactor.result("everything", json!({ "key": 1 })), json!("***"));
+        // A missing result stays visible as such.
+        assert_eq!(redactor.result("everything", Value::Null), Value::Null);
+
+        // Replaces the default redaction of the method.
+        assert_eq!(redactor.params("dumpprivkey", &params(&["addr"])), params(&["***"]));
+        assert_eq!(redactor.result("dumpprivkey", json!("key")), json!("key"));
+    }
+
+    #[test]
+    fn traces_redacted_calls() {
+        capture_logs();
+        let node = MockNode::new();
+        node.respond("walletpassphrase", Value::Null);
+        node.respond("signrawtransaction", json!({ "hex": "signed-tx-marker", "complete": true }));
+        node.respond("dumpprivkey", json!("dumped-key-marker"));
+        node.respond("z_getnewseed", json!("seed-result-marker"));
+        let client = node
+            .client()
+            .with_redaction("z_getnewseed", Redaction::params(&[0]).and_result());
+
+        let passphrase = SecretString::from("passphrase-marker");
+        client.walletpassphrase(&passphrase, Duration::from_secs(60)).unwrap();
+        let keys = ["private-key-marker".to_owned()];
+        let outputs: [DependentOutput; 0] = [];
+        client.signrawtransaction("unsigned-tx-marker", &outputs, &keys).unwrap();
+        assert_eq!(client.dumpprivkey("t1address-marker").unwrap(), "dumped-key-marker");
+        let seed: String = client.call("z_getnewseed", params(&["seed-param-marker"])).unwrap();
+        assert_eq!(seed, "seed-result-marker");
+
+        let expected = [
+            ("walletpassphrase", "60", "passphrase-marker"),
+            ("signrawtransaction", "signed-tx-marker", "private-key-marker"),
+            ("dumpprivkey", "t1address-marker", "dumped-key-marker"),
+            ("z_getnewseed", "***", "seed-param-marker"),
+        ];
+        for &(method, visible, secret) in &expected {
+            let logs = logs_of(method);
+            assert_eq!(logs.len(), 1, "{}", method);
+            assert!(logs[0].contains(visible), "{}", logs[0]);
+            assert!(logs[0].contains("***"), "{}", logs[0]);
+            assert!(!logs[0].contains(secret), "{}", logs[0]);
+        }
+        assert!(!logs_of("z_getnewseed")[0].contains("seed-result-marker"));
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use super::Error;
+
+// Methods with side effects a repeated call would duplicate.
+const NON_IDEMPOTENT: [&str; 18] = [
+    "sendtoaddress",
+    "sendmany",
+    "sendfrom",
+    "sendrawtransaction",
+    "getnewaddress",
+    "getrawchangeaddress",
+    "encryptwallet",
+    "z_sendmany",
+    "z_shieldcoinbase",
+    "z_mergetoaddress",
+    "z_getnewaddress",
+    "z_getnewaccount",
+    "z_getaddressforaccount",
+    "z_exportwallet",
+    "generate",
+    "generatetoaddress",
+    "keypoolrefill",
+    "backupwallet",
+];
+
+/// When and how often `Client` repeats a failed call.
+///
+/// Delays grow exponentially from `initial_backoff` up to `max_backoff`; with jitter
+/// each delay is randomly shortened by up to half. Calls of non-idempotent methods
+/// such as `sendtoaddress` are never repeated unless allowed with `retry_method`.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    rng: JitterRng,
+    is_transient: fn(&Error) -> bool,
+    excluded: HashSet<String>,
+}
+
+impl RetryPolicy {
+    /// Single attempt, the default of a new `Client`.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> RetryPolicy {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Replaces `Error::is_retryable` as the test for transient errors.
+    pub fn transient(mut self, is_transient: fn(&Error) -> bool) -> RetryPolicy {
+        self.is_transient = is_transient;
+        self
+    }
+
+    /// Allows repeating calls of a method excluded by default.
+    pub fn retry_method(mut self, method: &str) -> RetryPolicy {
+        self.excluded.remove(method);
+        self
+    }
+
+    pub fn exclude_method<S: Into<String>>(mut self, method: S) -> RetryPolicy {
+        self.excluded.insert(method.into());
+        self
+    }
+
+    pub(crate) fn should_retry(&self, method: &str, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts
+            && !self.excluded.contains(method)
+            && (self.is_transient)(error)
+    }
+
+    // Delay before the attempt following `attempt`.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        let delay = self
+            .initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+        if self.jitter {
+            delay - delay.mul_f64(self.rng.next_f64() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Five attempts with jittered backoff from 250 ms up to 10 s.
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            rng: JitterRng::new(),
+            is_transient: Error::is_retryable,
+            excluded: NON_IDEMPOTENT.iter().map(|method| method.to_string()).collect(),
+        }
+    }
+}
+
+// Xorshift64 generator for the jitter, seeded once per policy. Clones get their own seed
+// so that clients sharing a policy don't retry in lockstep.
+#[derive(Debug)]
+struct JitterRng(AtomicU64);
+
+impl JitterRng {
+    fn new() -> JitterRng {
+        let seed = RandomState::new().build_hasher().finish();
+        JitterRng(AtomicU64::new(seed | 1))
+    }
+
+    // Uniformly distributed in `0.0..1.0`.
+    fn next_f64(&self) -> f64 {
+        let step = |mut x: u64| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        let previous = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(step(x)))
+            .unwrap();
+        (step(previous) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Clone for JitterRng {
+    fn clone(&self) -> JitterRng {
+        JitterRng::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+    use Error;
+
+    #[test]
+    fn jitter_stays_within_half_the_delay() {
+        let policy = RetryPolicy::default().backoff(Duration::from_secs(1), Duration::from_secs(1));
+        let delays: Vec<Duration> = (0..100).map(|_| policy.delay(1)).collect();
+        for delay in &delays {
+            assert!(*delay >= Duration::from_millis(500) && *delay <= Duration::from_secs(1));
+        }
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+
+        let policy = policy.jitter(false);
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(40), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn non_idempotent_methods_are_not_retried() {
+        let error = Error::InWarmup(String::new());
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry("getblockcount", 1, &error));
+        assert!(!policy.should_retry("getblockcount", 5, &error));
+        let methods = [
+            "sendtoaddress",
+            "generate",
+            "generatetoaddress",
+            "keypoolrefill",
+            "backupwallet",
+        ];
+        for method in &methods {
+            assert!(!policy.should_retry(method, 1, &error));
+        }
+        assert!(policy.retry_method("generate").should_retry("generate", 1, &error));
+    }
+}
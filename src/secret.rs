@@ -0,0 +1,110 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+/// Key material or passphrase that is wiped from memory on drop and hidden from `Debug`.
+///
+/// Intermediate JSON buffers of the RPC layer are not covered; only the value held
+/// by this type is zeroized. Comparisons take the same time wherever the secrets
+/// differ; only their lengths may leak.
+#[derive(Clone)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+}
+
+impl<'a> From<&'a str> for SecretString {
+    fn from(secret: &'a str) -> SecretString {
+        SecretString(secret.to_owned())
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &SecretString) -> bool {
+        let (a, b) = (self.0.as_bytes(), other.0.as_bytes());
+        a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+    }
+}
+
+impl Eq for SecretString {}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SecretString, D::Error> {
+        String::deserialize(deserializer).map(SecretString)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use super::SecretString;
+
+    const SECRET: &str = "secret-extended-key-main1qwerty";
+
+    #[test]
+    fn debug_is_masked() {
+        let secret = SecretString::from(SECRET);
+        assert_eq!(format!("{:?}", secret), "SecretString(***)");
+        for debug in &[
+            format!("{:?}", Some(&secret)),
+            format!("{:#?}", vec![secret.clone()]),
+            format!("{:?}", (SECRET.len(), &secret)),
+        ] {
+            assert!(!debug.contains(SECRET), "{}", debug);
+            assert!(debug.contains("SecretString(***)"), "{}", debug);
+        }
+    }
+
+    #[test]
+    fn serializes_the_secret() {
+        let secret = SecretString::new(SECRET.to_owned());
+        assert_eq!(serde_json::to_value(&secret).unwrap(), json!(SECRET));
+        assert_eq!(serde_json::to_string(&[&secret]).unwrap(), format!("[\"{}\"]", SECRET));
+
+        let parsed: SecretString = serde_json::from_value(json!(SECRET)).unwrap();
+        assert_eq!(parsed.expose_secret(), SECRET);
+    }
+
+    #[test]
+    fn compares_contents() {
+        let secret = SecretString::from(SECRET);
+        assert_eq!(secret, SecretString::from(SECRET.to_owned()));
+        assert_ne!(secret, SecretString::from(&SECRET[1..]));
+        assert_ne!(secret, SecretString::from(SECRET.replace('y', "z")));
+        assert_ne!(secret, SecretString::from(""));
+        assert_eq!(SecretString::from(""), SecretString::from(""));
+    }
+}
@@ -0,0 +1,795 @@
+use std::fmt;
+use std::str::FromStr;
+
+use blake2b_simd::Params as Blake2bParams;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+
+use address::{Address, AddressError};
+use encoding::{base58check_decode, bech32_decode, bech32_encode, convert_bits, read_compact_size,
+               write_compact_size, Bech32Variant};
+use network::Network;
+
+const SAPLING_ADDRESS_LEN: usize = 43;
+const SPROUT_ADDRESS_LEN: usize = 64;
+const UA_PADDING_LEN: usize = 16;
+
+const TYPECODE_P2PKH: u32 = 0x00;
+const TYPECODE_P2SH: u32 = 0x01;
+const TYPECODE_SAPLING: u32 = 0x02;
+const TYPECODE_ORCHARD: u32 = 0x03;
+
+const ZCASH_NETWORKS: [Network; 3] = [
+    Network::ZcashMainnet,
+    Network::ZcashTestnet,
+    Network::ZcashRegtest,
+];
+
+fn sprout_prefix(network: Network) -> &'static [u8] {
+    if network == Network::ZcashMainnet {
+        &[0x16, 0x9a]
+    } else {
+        &[0x16, 0xb6]
+    }
+}
+
+/// Sprout payment address, holding the paying key `a_pk` and transmission key `pk_enc`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SproutAddress {
+    network: Network,
+    a_pk: [u8; 32],
+    pk_enc: [u8; 32],
+    encoded: String,
+}
+
+impl SproutAddress {
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn paying_key(&self) -> &[u8; 32] {
+        &self.a_pk
+    }
+
+    pub fn transmission_key(&self) -> &[u8; 32] {
+        &self.pk_enc
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl FromStr for SproutAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<SproutAddress, AddressError> {
+        let data = base58check_decode(s).ok_or(AddressError::Base58)?;
+        let network = [Network::ZcashMainnet, Network::ZcashTestnet]
+            .iter()
+            .cloned()
+            .find(|&network| data.starts_with(sprout_prefix(network)))
+            .ok_or(AddressError::UnknownPrefix)?;
+        if data.len() != 2 + SPROUT_ADDRESS_LEN {
+            return Err(AddressError::InvalidLength);
+        }
+        let mut a_pk = [0; 32];
+        let mut pk_enc = [0; 32];
+        a_pk.copy_from_slice(&data[2..34]);
+        pk_enc.copy_from_slice(&data[34..]);
+        Ok(SproutAddress {
+            network,
+            a_pk,
+            pk_enc,
+            encoded: s.to_owned(),
+        })
+    }
+}
+
+/// Sapling payment address: an 11 byte diversifier and the diversified transmission key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SaplingAddress {
+    network: Network,
+    raw: [u8; SAPLING_ADDRESS_LEN],
+    encoded: String,
+}
+
+impl SaplingAddress {
+    pub fn from_raw(network: Network, raw: [u8; SAPLING_ADDRESS_LEN]) -> Result<SaplingAddress, AddressError> {
+        let hrp = network
+            .sapling_hrp()
+            .ok_or(AddressError::NetworkMismatch(network))?;
+        let data = convert_bits(&raw, 8, 5, true).unwrap();
+        Ok(SaplingAddress {
+            network,
+            raw,
+            encoded: bech32_encode(hrp, &data, Bech32Variant::Bech32),
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn diversifier(&self) -> &[u8] {
+        &self.raw[..11]
+    }
+
+    pub fn pk_d(&self) -> &[u8] {
+        &self.raw[11..]
+    }
+
+    pub fn to_raw(&self) -> [u8; SAPLING_ADDRESS_LEN] {
+        self.raw
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl FromStr for SaplingAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<SaplingAddress, AddressError> {
+        let (hrp, data, variant) = bech32_decode(s).ok_or(AddressError::Bech32)?;
+        let network = ZCASH_NETWORKS
+            .iter()
+            .cloned()
+            .find(|network| network.sapling_hrp() == Some(hrp.as_str()))
+            .ok_or(AddressError::UnknownPrefix)?;
+        if variant != Bech32Variant::Bech32 {
+            return Err(AddressError::Bech32);
+        }
+        let data = convert_bits(&data, 5, 8, false).ok_or(AddressError::Bech32)?;
+        if data.len() != SAPLING_ADDRESS_LEN {
+            return Err(AddressError::InvalidLength);
+        }
+        let mut raw = [0; SAPLING_ADDRESS_LEN];
+        raw.copy_from_slice(&data);
+        Ok(SaplingAddress {
+            network,
+            raw,
+            encoded: s.to_ascii_lowercase(),
+        })
+    }
+}
+
+/// Receiver contained in a Unified Address.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Receiver {
+    P2pkh([u8; 20]),
+    P2sh([u8; 20]),
+    Sapling([u8; SAPLING_ADDRESS_LEN]),
+    Orchard([u8; SAPLING_ADDRESS_LEN]),
+    Unknown { typecode: u32, data: Vec<u8> },
+}
+
+impl Receiver {
+    pub fn typecode(&self) -> u32 {
+        match *self {
+            Receiver::P2pkh(_) => TYPECODE_P2PKH,
+            Receiver::P2sh(_) => TYPECODE_P2SH,
+            Receiver::Sapling(_) => TYPECODE_SAPLING,
+            Receiver::Orchard(_) => TYPECODE_ORCHARD,
+            Receiver::Unknown { typecode, .. } => typecode,
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        match *self {
+            Receiver::P2pkh(ref data) | Receiver::P2sh(ref data) => data,
+            Receiver::Sapling(ref data) | Receiver::Orchard(ref data) => data,
+            Receiver::Unknown { ref data, .. } => data,
+        }
+    }
+
+    fn is_transparent(&self) -> bool {
+        matches!(*self, Receiver::P2pkh(_) | Receiver::P2sh(_))
+    }
+
+    fn from_parts(typecode: u32, data: &[u8]) -> Result<Receiver, AddressError> {
+        fn fixed<T: Default + AsMut<[u8]>>(data: &[u8]) -> Result<T, AddressError> {
+            let mut raw = T::default();
+            if raw.as_mut().len() != data.len() {
+                return Err(AddressError::InvalidLength);
+            }
+            raw.as_mut().copy_from_slice(data);
+            Ok(raw)
+        }
+
+        Ok(match typecode {
+            TYPECODE_P2PKH => Receiver::P2pkh(fixed(data)?),
+            TYPECODE_P2SH => Receiver::P2sh(fixed(data)?),
+            TYPECODE_SAPLING | TYPECODE_ORCHARD => {
+                if data.len() != SAPLING_ADDRESS_LEN {
+                    return Err(AddressError::InvalidLength);
+                }
+                let mut raw = [0; SAPLING_ADDRESS_LEN];
+                raw.copy_from_slice(data);
+                if typecode == TYPECODE_SAPLING {
+                    Receiver::Sapling(raw)
+                } else {
+                    Receiver::Orchard(raw)
+                }
+            }
+            typecode => Receiver::Unknown {
+                typecode,
+                data: data.to_vec(),
+            },
+        })
+    }
+}
+
+/// ZIP 316 Unified Address bundling transparent, Sapling and Orchard receivers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnifiedAddress {
+    network: Network,
+    receivers: Vec<Receiver>,
+    encoded: String,
+}
+
+impl UnifiedAddress {
+    /// Builds an address from receivers, which are sorted into typecode order.
+    pub fn new(network: Network, mut receivers: Vec<Receiver>) -> Result<UnifiedAddress, AddressError> {
+        let hrp = network
+            .unified_hrp()
+            .ok_or(AddressError::NetworkMismatch(network))?;
+        receivers.sort_by_key(Receiver::typecode);
+        check_receivers(&receivers)?;
+
+        let mut raw = Vec::new();
+        for receiver in &receivers {
+            write_compact_size(&mut raw, u64::from(receiver.typecode()));
+            write_compact_size(&mut raw, receiver.data().len() as u64);
+            raw.extend_from_slice(receiver.data());
+        }
+        raw.extend_from_slice(&ua_padding(hrp));
+        f4jumble(&mut raw)?;
+
+        let data = convert_bits(&raw, 8, 5, true).unwrap();
+        Ok(UnifiedAddress {
+            network,
+            receivers,
+            encoded: bech32_encode(hrp, &data, Bech32Variant::Bech32m),
+        })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn receivers(&self) -> &[Receiver] {
+        &self.receivers
+    }
+
+    /// Returns the transparent receiver as a standalone address.
+    pub fn transparent(&self) -> Option<Address> {
+        self.receivers.iter().find_map(|receiver| match *receiver {
+            Receiver::P2pkh(hash) => Some(Address::p2pkh(self.network, hash)),
+            Receiver::P2sh(hash) => Some(Address::p2sh(self.network, hash)),
+            _ => None,
+        })
+    }
+
+    /// Returns the Sapling receiver as a standalone address.
+    pub fn sapling(&self) -> Option<SaplingAddress> {
+        self.receivers.iter().find_map(|receiver| match *receiver {
+            Receiver::Sapling(raw) => SaplingAddress::from_raw(self.network, raw).ok(),
+            _ => None,
+        })
+    }
+
+    /// Returns the raw 43 byte Orchard receiver.
+    pub fn orchard(&self) -> Option<&[u8; SAPLING_ADDRESS_LEN]> {
+        self.receivers.iter().find_map(|receiver| match *receiver {
+            Receiver::Orchard(ref raw) => Some(raw),
+            _ => None,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl FromStr for UnifiedAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<UnifiedAddress, AddressError> {
+        let (hrp, data, variant) = bech32_decode(s).ok_or(AddressError::Bech32)?;
+        let network = ZCASH_NETWORKS
+            .iter()
+            .cloned()
+            .find(|network| network.unified_hrp() == Some(hrp.as_str()))
+            .ok_or(AddressError::UnknownPrefix)?;
+        if variant != Bech32Variant::Bech32m {
+            return Err(AddressError::Bech32);
+        }
+        let mut raw = convert_bits(&data, 5, 8, false).ok_or(AddressError::Bech32)?;
+        f4jumble_inv(&mut raw)?;
+
+        let padding_start = raw.len() - UA_PADDING_LEN;
+        if raw[padding_start..] != ua_padding(&hrp)[..] {
+            return Err(AddressError::InvalidLength);
+        }
+        let mut cursor = &raw[..padding_start];
+        let mut receivers = Vec::new();
+        while !cursor.is_empty() {
+            let typecode = read_compact_size(&mut cursor).ok_or(AddressError::InvalidLength)?;
+            let len = read_compact_size(&mut cursor).ok_or(AddressError::InvalidLength)? as usize;
+            if typecode > u64::from(u32::MAX) || len > cursor.len() {
+                return Err(AddressError::InvalidLength);
+            }
+            receivers.push(Receiver::from_parts(typecode as u32, &cursor[..len])?);
+            cursor = &cursor[len..];
+        }
+        if receivers
+            .windows(2)
+            .any(|pair| pair[0].typecode() >= pair[1].typecode())
+        {
+            return Err(AddressError::InvalidReceivers);
+        }
+        check_receivers(&receivers)?;
+
+        Ok(UnifiedAddress {
+            network,
+            receivers,
+            encoded: s.to_ascii_lowercase(),
+        })
+    }
+}
+
+fn check_receivers(receivers: &[Receiver]) -> Result<(), AddressError> {
+    let has = |typecode| receivers.iter().any(|r| r.typecode() == typecode);
+    let mut typecodes: Vec<u32> = receivers.iter().map(Receiver::typecode).collect();
+    typecodes.dedup();
+    if typecodes.len() != receivers.len()
+        || (has(TYPECODE_P2PKH) && has(TYPECODE_P2SH))
+        || receivers.iter().all(Receiver::is_transparent)
+    {
+        return Err(AddressError::InvalidReceivers);
+    }
+    Ok(())
+}
+
+fn ua_padding(hrp: &str) -> [u8; UA_PADDING_LEN] {
+    let mut padding = [0; UA_PADDING_LEN];
+    padding[..hrp.len()].copy_from_slice(hrp.as_bytes());
+    padding
+}
+
+// F4Jumble from ZIP 316, an unkeyed 4-round Feistel construction over BLAKE2b.
+const F4JUMBLE_MIN_LEN: usize = 38;
+const F4JUMBLE_MAX_LEN: usize = 4_194_368;
+const BLAKE2B_OUTBYTES: usize = 64;
+
+fn f4jumble_h(left: &mut [u8], right: &[u8], round: u8) {
+    let mut personal = *b"UA_F4Jumble_H\0\0\0";
+    personal[13] = round;
+    let hash = Blake2bParams::new()
+        .hash_length(left.len())
+        .personal(&personal)
+        .hash(right);
+    xor(left, hash.as_bytes());
+}
+
+fn f4jumble_g(left: &[u8], right: &mut [u8], round: u8) {
+    for (j, chunk) in right.chunks_mut(BLAKE2B_OUTBYTES).enumerate() {
+        let mut personal = *b"UA_F4Jumble_G\0\0\0";
+        personal[13] = round;
+        personal[14..].copy_from_slice(&(j as u16).to_le_bytes());
+        let hash = Blake2bParams::new()
+            .hash_length(BLAKE2B_OUTBYTES)
+            .personal(&personal)
+            .hash(left);
+        xor(chunk, hash.as_bytes());
+    }
+}
+
+fn xor(target: &mut [u8], source: &[u8]) {
+    for (target, source) in target.iter_mut().zip(source) {
+        *target ^= source;
+    }
+}
+
+fn f4jumble_split(message: &mut [u8]) -> Result<(&mut [u8], &mut [u8]), AddressError> {
+    if message.len() < F4JUMBLE_MIN_LEN || message.len() > F4JUMBLE_MAX_LEN {
+        return Err(AddressError::InvalidLength);
+    }
+    let left_len = ::std::cmp::min(BLAKE2B_OUTBYTES, message.len() / 2);
+    Ok(message.split_at_mut(left_len))
+}
+
+fn f4jumble(message: &mut [u8]) -> Result<(), AddressError> {
+    let (left, right) = f4jumble_split(message)?;
+    f4jumble_g(left, right, 0);
+    f4jumble_h(left, right, 0);
+    f4jumble_g(left, right, 1);
+    f4jumble_h(left, right, 1);
+    Ok(())
+}
+
+fn f4jumble_inv(message: &mut [u8]) -> Result<(), AddressError> {
+    let (left, right) = f4jumble_split(message)?;
+    f4jumble_h(left, right, 1);
+    f4jumble_g(left, right, 1);
+    f4jumble_h(left, right, 0);
+    f4jumble_g(left, right, 0);
+    Ok(())
+}
+
+/// Any shielded payment address accepted by the `z_*` RPCs.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShieldedAddress {
+    Sprout(SproutAddress),
+    Sapling(SaplingAddress),
+    Unified(UnifiedAddress),
+}
+
+impl ShieldedAddress {
+    /// Parses an address and checks that it belongs to `network`.
+    pub fn parse_for(s: &str, network: Network) -> Result<ShieldedAddress, AddressError> {
+        let address: ShieldedAddress = s.parse()?;
+        let matches = match address {
+            // Sprout addresses share one prefix between testnet and regtest.
+            ShieldedAddress::Sprout(ref sprout) => {
+                sprout_prefix(sprout.network) == sprout_prefix(network) && network.is_zcash()
+            }
+            _ => address.network() == network,
+        };
+        if matches {
+            Ok(address)
+        } else {
+            Err(AddressError::NetworkMismatch(network))
+        }
+    }
+
+    pub fn network(&self) -> Network {
+        match *self {
+            ShieldedAddress::Sprout(ref address) => address.network(),
+            ShieldedAddress::Sapling(ref address) => address.network(),
+            ShieldedAddress::Unified(ref address) => address.network(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match *self {
+            ShieldedAddress::Sprout(ref address) => address.as_str(),
+            ShieldedAddress::Sapling(ref address) => address.as_str(),
+            ShieldedAddress::Unified(ref address) => address.as_str(),
+        }
+    }
+}
+
+impl FromStr for ShieldedAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<ShieldedAddress, AddressError> {
+        if let Ok(address) = s.parse() {
+            return Ok(ShieldedAddress::Sapling(address));
+        }
+        if let Ok(address) = s.parse() {
+            return Ok(ShieldedAddress::Unified(address));
+        }
+        s.parse().map(ShieldedAddress::Sprout)
+    }
+}
+
+macro_rules! impl_address_traits {
+    ($($name:ident),*) => {
+        $(
+            impl fmt::Display for $name {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+
+            impl AsRef<str> for $name {
+                fn as_ref(&self) -> &str {
+                    self.as_str()
+                }
+            }
+
+            impl Serialize for $name {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(self.as_str())
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $name {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                    let s = String::deserialize(deserializer)?;
+                    s.parse().map_err(de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+impl_address_traits!(SproutAddress, SaplingAddress, UnifiedAddress, ShieldedAddress);
+
+#[cfg(test)]
+mod tests {
+    use blake2b_simd::blake2b;
+
+    use super::{f4jumble, f4jumble_inv, ua_padding, Receiver, UnifiedAddress, F4JUMBLE_MAX_LEN,
+                F4JUMBLE_MIN_LEN};
+    use address::AddressError;
+    use encoding::{bech32_encode, convert_bits, write_compact_size, Bech32Variant};
+    use network::Network;
+
+    // F4Jumble vectors from zcash-test-vectors, as (message, jumbled) pairs.
+    const F4JUMBLE_VECTORS: &[(&str, &str)] = &[
+        (
+            "5d7a8f739a2d9e945b0ce152a8049e294c4d6e66b164939daffa2ef6ee6921481cdd86b3cc4318d9\
+            614fc820905d042b",
+            "0304d029141b995da5387c125970673504d6c764d91ea6c082123770c7139ccd88ee27368cd0c092\
+            1a0444c8e5858d22",
+        ),
+        (
+            "b1ef9ca3f24988c7b3534201cfb1cd8dbf69b8250c18ef41294ca97993db546c1fe01f7e9c8e36d6\
+            a5e29d4e30a73594bf5098421c69378af1e40f64e125946f",
+            "5271fa3321f3adbcfb075196883d542b438ec6339176537daf859841fe6a56222bff76d1662b5509\
+            a9e1079e446eeedd2e683c31aae3ee1851d7954328526be1",
+        ),
+        (
+            "25c9a138f49b1a537edcf04be34a9851a7af9db6990ed83dd64af3597c04323ea51b0052ad8084a8\
+            b9da948d320dadd64f5431e61ddf658d24ae67c22c8d1309131fc00fe7f235734276d38d47f1e191\
+            e00c7a1d48af046827591e9733a97fa6b679f3dc601d008285edcbdae69ce8fc1be4aac00ff2711e\
+            bd931de518856878f7",
+            "7508a3a146714f229db91b543e240633ed57853f6451c9db6d64c6e86af1b88b28704f608582c53c\
+            51ce7d5b8548827a971d2b98d41b7f6258655902440cd66ee11e84dbfac7d2a43696fd0468810a3d\
+            9637c3fa58e7d2d341ef250fa09b9fb71a78a41d389370138a55ea58fcde779d714a04e0d30e61dc\
+            2d8be0da61cd684509",
+        ),
+    ];
+
+    // BLAKE2b-512 hash of the jumbled maximum length message from the long F4Jumble vectors,
+    // where the message byte at each index is `i as u8`.
+    const F4JUMBLE_MAX_LEN_HASH: &str =
+        "a5f18f163e598d4adb6ea7248057e24c1b61f29b33b7abcdabd420a0f2ee6c3ed31394652f28b59c\
+        44d3ea9ecf85f4d501e6aac14df288efd62cf80d1829d025";
+
+    // ZIP 316 unified address vectors from zcash-test-vectors, with their receivers as
+    // (typecode, data) pairs in encoding order.
+    const UA_VECTORS: &[(&str, &[(u32, &str)])] = &[
+        (
+            "u1l8xunezsvhq8fgzfl7404m450nwnd76zshscn6nfys7vyz2ywyh4cc5daaq0c7q2su5lqfh23sp7fk\
+            f3kt27ve5948mzpfdvckzaect2jtte308mkwlycj2u0eac077wu70vqcetkxf",
+            &[
+                (0, "7bb83570b8fae146e03c5331a020b1e0892f631d"),
+                (2, "d8ef8293d26de832e7193f296ba1922d90f122c6135bc231eebd91efdb03b1a8606771cd4fd64805\
+                74d43e"),
+            ],
+        ),
+        (
+            "u1pg2aaph7jp8rpf6yhsza25722sg5fcn3vaca6ze27hqjw7jvvhhuxkpcg0ge9xh6drsgdkda8qjq5c\
+            hpehkcpxf87rnjryjqwymdheptpvnljqqrjqzjwkc2ma6hcq666kgwfytxwac8eyex6ndgr6ezte6670\
+            6e3vaqrd25dzvzkc69kw0jgywtd0cmq52q5lkw6uh7hyvzjse8ksx",
+            &[
+                (0, "cad268758c5e71493066446b98e71df9d1d6a5ca"),
+                (2, "9f6e0bf90a18fc0b9b83ae9f23ad4358648638482b5def8975635b66fd8a708335f9235a3186ec0f\
+                033f84"),
+                (3, "cecbe5e689a453a3fe10ccf7617e6c1fb382819d7fc9200a1f42092ac84a30378f8c1fb90dff71a6\
+                d5042d"),
+            ],
+        ),
+        (
+            "u1ay3aawlldjrmxqnjf5medr5ma6p3acnet464ht8lmwplq5cd3ugytcmlf96rrmtgwldc75x94qn4n8\
+            pgen36y8tywlq6yjk7lkf3fa8wzjrav8z2xpxqnrnmjxh8tmz6jhfh425t7f3vy6p4pd3zmqayq49efl\
+            2c4xydc0gszg660q9p",
+            &[
+                (2, "88533c398a49c2513dc85162bf220abaf47dc983f14e908ddaaa7322dba16531bc62efe750fe575c\
+                8d149b"),
+                (3, "953f3c78d103c32b60559299462ebb27348964b892acad10482fe502c99f0d524959ba7be4f188e3\
+                a27138"),
+            ],
+        ),
+        (
+            "u1sem2gcey0emntrvxyjv8hyhq0w5fr4sxaj3cppgrfqgg6laydh8m78gy2cw2p54zzak3alnnsx4xju\
+            hazpkrfcd90wl0c7ldj6y095hh5j6j2evry9vg5jqp4dyqpwqeryu7pes4sxyyyqwn6egs5daxk4473v\
+            9xpgzrwv5n0tvs93nlj4xpphq4vs2w8um9ph7zkte08t7fa509mnrt9apuhr22xq34mp2svjnq6rvfn0\
+            hg6lkehxtlj39vgjxjlkjfhx8rw2f02ckq8k5szcxsnhkgr2cqlmf2udl2gqdqr5t6",
+            &[
+                (2, "da2672c010f7364df6fad49dd39be0e4d4be73c45e239448fcc385cc68094bf36ddbc4ec0219b567\
+                955556"),
+                (65533, "d17d19f3355bcf73cecb8cb8a5da01307152f13936a270572670dc82d39026c6cb4cd4b0f7f5aa2a\
+                4f5a5341ec5dd715406f2fdd2afa733f5f641c8c21862a1bafce2609d9eecfa158cfb5cd79f88008\
+                e315dc7d8388e76c1782fd2795d18a763624c25fa959cc97489ce75745824b77868c53239cfbdf73\
+                ca"),
+            ],
+        ),
+        (
+            "u1ddnjsdcpm36r6aq79n3s68shjweksnmwtdltrh046s8m6xcws9ygyawalxx8n6hg6vegk0wh8zjnaf\
+            xgh6msppjsljvyt0ynece3lvm0",
+            &[
+                (3, "e340636542ece1c81285ed4eab448adbb5a8c0f4d386eeff337e88e6915f6c3ec1b6ea835a88d566\
+                12d2bd"),
+            ],
+        ),
+        (
+            "u1xdrenc94696j8clxa2xnkdg8xd5t3y8s24urctyxu87vggv0u46qr4lkpnh7gqqdev9wwugt6xkv8c\
+            8du8ufhfl8nfjnzusf6cw20wpm85hlshmnmj2lkyhka9rua7qw7kr0xeajk7y2rlsuwl6z6l5l3wq3v6\
+            rrqt9e8zy7sc7pww45jznrj4xy6h9rp4kjy5xtl5upr30u4cyk58kv3t80k3p8w97k3e345h7avmjylx\
+            akx6sgyk5ss8th5kqay50ewav62eeep7tghzejaflsdstpwz55haex398jqpq27007me2",
+            &[
+                (2, "99ae333db1074fca1a6a94bed3ea548c1db2512dfbe75af9e84c162260b4813bb6bdb4443969daa5\
+                713ff1"),
+                (3, "cdf7fed0d0822fd849cffb20a4d5ee701ad8141e66d81ddfabf87875117c05092240603c546b8dc1\
+                87cd8c"),
+                (65532, "657b43ee8da645443814cc7329f3e9b4e54c236c29af3923101756d9fa4bd0f7d2ddaacb6b0f86a2\
+                658e0a07a05ac5b950051cd24c47a88d13d659ba2a46ca1830816d09cd7646f76f716abec5de"),
+            ],
+        ),
+        (
+            "u187vrwl4ampyxd5m6aj38n4ndkmj8v6gs97hkt23aps3sn5k89a0gk2smluexgdprcrtm56ezc5c7tj\
+            wlrnnl79tjtrxmqd42c5mpyz7g",
+            &[
+                (2, "eee19641bc6b802f353eb793f728b17a277ef0358696a24a7122bc56537b229647f3810d27ce4522\
+                7c6f39"),
+            ],
+        ),
+    ];
+
+    fn receiver(typecode: u32, data: &str) -> Receiver {
+        Receiver::from_parts(typecode, &hex::decode(data).unwrap()).unwrap()
+    }
+
+    // Encodes receivers in the given order with arbitrary padding, bypassing the checks
+    // in `UnifiedAddress::new`.
+    fn encode_raw(receivers: &[Receiver], padding: &[u8]) -> String {
+        let mut raw = Vec::new();
+        for receiver in receivers {
+            write_compact_size(&mut raw, u64::from(receiver.typecode()));
+            write_compact_size(&mut raw, receiver.data().len() as u64);
+            raw.extend_from_slice(receiver.data());
+        }
+        raw.extend_from_slice(padding);
+        f4jumble(&mut raw).unwrap();
+        bech32_encode("u", &convert_bits(&raw, 8, 5, true).unwrap(), Bech32Variant::Bech32m)
+    }
+
+    #[test]
+    fn f4jumble_vectors() {
+        for &(normal, jumbled) in F4JUMBLE_VECTORS {
+            let mut message = hex::decode(normal).unwrap();
+            f4jumble(&mut message).unwrap();
+            assert_eq!(hex::encode(&message), jumbled);
+            f4jumble_inv(&mut message).unwrap();
+            assert_eq!(hex::encode(&message), normal);
+        }
+    }
+
+    // Takes seconds in debug builds; run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn f4jumble_max_len_vector() {
+        let normal: Vec<u8> = (0..F4JUMBLE_MAX_LEN).map(|i| i as u8).collect();
+        let mut message = normal.clone();
+        f4jumble(&mut message).unwrap();
+        assert_eq!(hex::encode(blake2b(&message).as_bytes()), F4JUMBLE_MAX_LEN_HASH);
+        f4jumble_inv(&mut message).unwrap();
+        assert!(message == normal);
+    }
+
+    #[test]
+    fn f4jumble_length_bounds() {
+        for &len in &[F4JUMBLE_MIN_LEN, F4JUMBLE_MIN_LEN + 1, 128, 129] {
+            let normal: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut message = normal.clone();
+            f4jumble(&mut message).unwrap();
+            assert_ne!(message, normal);
+            f4jumble_inv(&mut message).unwrap();
+            assert_eq!(message, normal);
+        }
+        for &len in &[0, F4JUMBLE_MIN_LEN - 1, F4JUMBLE_MAX_LEN + 1] {
+            let mut message = vec![0; len];
+            assert_eq!(f4jumble(&mut message), Err(AddressError::InvalidLength));
+            assert_eq!(f4jumble_inv(&mut message), Err(AddressError::InvalidLength));
+        }
+    }
+
+    #[test]
+    fn unified_address_vectors() {
+        for &(s, expected) in UA_VECTORS {
+            let receivers: Vec<Receiver> = expected
+                .iter()
+                .map(|&(typecode, data)| receiver(typecode, data))
+                .collect();
+
+            let address: UnifiedAddress = s.parse().unwrap_or_else(|e| panic!("{}: {}", s, e));
+            assert_eq!(address.network(), Network::ZcashMainnet);
+            assert_eq!(address.receivers(), &receivers[..]);
+            assert_eq!(address.as_str(), s);
+
+            // Receivers are sorted into typecode order before encoding.
+            let reversed = receivers.iter().rev().cloned().collect();
+            let encoded = UnifiedAddress::new(Network::ZcashMainnet, reversed).unwrap();
+            assert_eq!(encoded.as_str(), s);
+            assert_eq!(encoded, address);
+        }
+    }
+
+    #[test]
+    fn unified_address_padding() {
+        let receivers = [Receiver::Orchard([1; 43])];
+        let valid = encode_raw(&receivers, &ua_padding("u"));
+        assert_eq!(
+            valid,
+            UnifiedAddress::new(Network::ZcashMainnet, receivers.to_vec())
+                .unwrap()
+                .as_str()
+        );
+        assert!(valid.parse::<UnifiedAddress>().is_ok());
+
+        let invalid = [
+            encode_raw(&receivers, &[0xff; 16]),
+            encode_raw(&receivers, &ua_padding("u")[..15]),
+            encode_raw(&receivers, &ua_padding("utest")),
+        ];
+        for s in &invalid {
+            assert_eq!(s.parse::<UnifiedAddress>(), Err(AddressError::InvalidLength));
+        }
+
+        let testnet = UnifiedAddress::new(Network::ZcashTestnet, receivers.to_vec()).unwrap();
+        assert!(testnet.as_str().starts_with("utest1"));
+        assert_eq!(testnet.as_str().parse(), Ok(testnet.clone()));
+    }
+
+    #[test]
+    fn unified_address_truncated_receivers() {
+        let mut raw = Vec::new();
+        write_compact_size(&mut raw, 3);
+        write_compact_size(&mut raw, 43);
+        raw.extend_from_slice(&[1; 43]);
+        write_compact_size(&mut raw, 2);
+        write_compact_size(&mut raw, 43);
+        raw.extend_from_slice(&[2; 42]);
+        raw.extend_from_slice(&ua_padding("u"));
+        f4jumble(&mut raw).unwrap();
+        let s = bech32_encode("u", &convert_bits(&raw, 8, 5, true).unwrap(), Bech32Variant::Bech32m);
+        assert_eq!(s.parse::<UnifiedAddress>(), Err(AddressError::InvalidLength));
+    }
+
+    #[test]
+    fn unified_address_receiver_ordering() {
+        let invalid = [
+            vec![Receiver::Sapling([0; 43]), Receiver::P2pkh([0; 20])],
+            vec![Receiver::Orchard([0; 43]), Receiver::Sapling([0; 43])],
+            vec![Receiver::Sapling([1; 43]), Receiver::Sapling([2; 43])],
+            vec![Receiver::P2pkh([0; 20]), Receiver::P2sh([0; 20]), Receiver::Sapling([0; 43])],
+            vec![Receiver::P2pkh([0; 20]), Receiver::P2sh([0; 20])],
+        ];
+        for receivers in &invalid {
+            let s = encode_raw(receivers, &ua_padding("u"));
+            assert_eq!(s.parse::<UnifiedAddress>(), Err(AddressError::InvalidReceivers));
+        }
+
+        let unsorted = vec![
+            Receiver::P2pkh([0; 20]),
+            Receiver::Unknown {
+                typecode: 0xff,
+                data: vec![],
+            },
+            Receiver::Orchard([0; 43]),
+            Receiver::Sapling([0; 43]),
+        ];
+        let address = UnifiedAddress::new(Network::ZcashMainnet, unsorted).unwrap();
+        let typecodes: Vec<u32> = address.receivers().iter().map(Receiver::typecode).collect();
+        assert_eq!(typecodes, [0, 2, 3, 0xff]);
+        assert_eq!(address.as_str().parse(), Ok(address.clone()));
+
+        let invalid = [
+            vec![Receiver::P2pkh([0; 20])],
+            vec![Receiver::Sapling([1; 43]), Receiver::Sapling([2; 43])],
+            vec![Receiver::P2pkh([0; 20]), Receiver::P2sh([0; 20]), Receiver::Orchard([0; 43])],
+        ];
+        for receivers in &invalid {
+            assert_eq!(
+                UnifiedAddress::new(Network::ZcashMainnet, receivers.clone()),
+                Err(AddressError::InvalidReceivers)
+            );
+        }
+    }
+}
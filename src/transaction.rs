@@ -0,0 +1,1554 @@
+//! Offline parsing and serialization of raw Bitcoin and Zcash transactions.
+//!
+//! Supports Bitcoin legacy and segwit transactions, pre-Overwinter Zcash transactions
+//! with Sprout JoinSplits, and Zcash v3 (Overwinter), v4 (Sapling) and v5 (NU5)
+//! transactions. Parsed transactions serialize back to the exact same bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
+use failure::Fail;
+use hex;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+
+use amount::Amount;
+use encoding::{read_compact_size, sha256d, write_compact_size};
+
+const OVERWINTERED_FLAG: u32 = 1 << 31;
+const OVERWINTER_VERSION_GROUP_ID: u32 = 0x03C4_8270;
+const SAPLING_VERSION_GROUP_ID: u32 = 0x892F_2085;
+const NU5_VERSION_GROUP_ID: u32 = 0x26A7_270A;
+
+// Upper bound of any length prefix, as enforced by both nodes.
+const MAX_SIZE: usize = 0x0200_0000;
+
+const BCTV14_PROOF_LEN: usize = 296;
+const GROTH_PROOF_LEN: usize = 192;
+const SPROUT_CIPHERTEXT_LEN: usize = 601;
+const ENC_CIPHERTEXT_LEN: usize = 580;
+const OUT_CIPHERTEXT_LEN: usize = 80;
+
+/// Error of parsing a raw transaction.
+#[derive(Debug, Display, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    #[display(fmt = "Invalid hex encoding.")]
+    Hex,
+    #[display(fmt = "Unexpected end of transaction data.")]
+    UnexpectedEnd,
+    #[display(fmt = "{} bytes left after the end of the transaction.", _0)]
+    TrailingData(usize),
+    #[display(fmt = "Invalid length prefix.")]
+    InvalidLength,
+    #[display(fmt = "Unsupported transaction version {}.", _0)]
+    UnsupportedVersion(u32),
+    #[display(fmt = "Unknown version group id {:#010x}.", _0)]
+    UnknownVersionGroup(u32),
+    #[display(fmt = "Invalid witness data.")]
+    InvalidWitness,
+    #[display(fmt = "Value balance set without shielded spends or outputs.")]
+    UnexpectedValueBalance,
+}
+
+impl Fail for TransactionError {}
+
+/// Reference to an output of a previous transaction.
+///
+/// `txid` is kept in internal byte order, reversed relative to the hex shown by nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OutPoint {
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+impl OutPoint {
+    /// Creates an outpoint from a txid in the hex form used by RPC calls.
+    pub fn new(txid: &str, vout: u32) -> Result<OutPoint, TransactionError> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(txid, &mut bytes).map_err(|_| TransactionError::Hex)?;
+        bytes.reverse();
+        Ok(OutPoint { txid: bytes, vout })
+    }
+
+    /// Txid in the hex form used by RPC calls.
+    pub fn txid_hex(&self) -> String {
+        reversed_hex(&self.txid)
+    }
+
+    /// Whether this is the outpoint spent by coinbase transactions.
+    pub fn is_null(&self) -> bool {
+        self.txid == [0; 32] && self.vout == u32::MAX
+    }
+}
+
+/// Transparent input, the only kind of input on Bitcoin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransparentInput {
+    pub prevout: OutPoint,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    /// Segwit witness stack; always empty on Zcash.
+    pub witness: Vec<Vec<u8>>,
+}
+
+/// Transparent output, the only kind of output on Bitcoin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransparentOutput {
+    pub value: Amount,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// Sprout JoinSplit, moving value in and out of the Sprout pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JoinSplitDescription {
+    pub vpub_old: Amount,
+    pub vpub_new: Amount,
+    pub anchor: [u8; 32],
+    pub nullifiers: [[u8; 32]; 2],
+    pub commitments: [[u8; 32]; 2],
+    pub ephemeral_key: [u8; 32],
+    pub random_seed: [u8; 32],
+    pub macs: [[u8; 32]; 2],
+    /// BCTV14 proof before v4, Groth16 proof from v4 on.
+    pub proof: Vec<u8>,
+    pub ciphertexts: [[u8; SPROUT_CIPHERTEXT_LEN]; 2],
+}
+
+/// JoinSplits of a transaction with their signing key and signature.
+///
+/// Pre-Overwinter Zcash transactions always carry this bundle, even when empty, which
+/// tells them apart from Bitcoin transactions of the same version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JoinSplitData {
+    pub joinsplits: Vec<JoinSplitDescription>,
+    pub pubkey: [u8; 32],
+    pub sig: [u8; 64],
+}
+
+/// Sapling spend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpendDescription {
+    pub cv: [u8; 32],
+    pub anchor: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub rk: [u8; 32],
+    pub zkproof: [u8; GROTH_PROOF_LEN],
+    pub spend_auth_sig: [u8; 64],
+}
+
+/// Sapling output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputDescription {
+    pub cv: [u8; 32],
+    pub cmu: [u8; 32],
+    pub ephemeral_key: [u8; 32],
+    pub enc_ciphertext: [u8; ENC_CIPHERTEXT_LEN],
+    pub out_ciphertext: [u8; OUT_CIPHERTEXT_LEN],
+    pub zkproof: [u8; GROTH_PROOF_LEN],
+}
+
+/// Sapling spends and outputs of a transaction.
+///
+/// v5 transactions share one anchor between all spends; it is taken from the first spend
+/// when serializing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaplingShieldedData {
+    pub spends: Vec<SpendDescription>,
+    pub outputs: Vec<OutputDescription>,
+    pub value_balance: Amount,
+    pub binding_sig: [u8; 64],
+}
+
+/// Orchard action, spending one note and creating another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActionDescription {
+    pub cv: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub rk: [u8; 32],
+    pub cmx: [u8; 32],
+    pub ephemeral_key: [u8; 32],
+    pub enc_ciphertext: [u8; ENC_CIPHERTEXT_LEN],
+    pub out_ciphertext: [u8; OUT_CIPHERTEXT_LEN],
+    pub spend_auth_sig: [u8; 64],
+}
+
+/// Orchard actions of a v5 transaction with their shared proof and signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrchardShieldedData {
+    pub actions: Vec<ActionDescription>,
+    pub flags: u8,
+    pub value_balance: Amount,
+    pub anchor: [u8; 32],
+    pub proof: Vec<u8>,
+    pub binding_sig: [u8; 64],
+}
+
+/// Raw Bitcoin or Zcash transaction, parsed offline.
+///
+/// Converts to and from the hex strings taken and returned by `getrawtransaction`,
+/// `sendrawtransaction` and friends, and serializes to JSON as such a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    /// Version without the Overwinter flag.
+    pub version: u32,
+    /// Set on Overwinter and later Zcash transactions.
+    pub version_group_id: Option<u32>,
+    /// Only serialized by v5 transactions.
+    pub consensus_branch_id: u32,
+    pub inputs: Vec<TransparentInput>,
+    pub outputs: Vec<TransparentOutput>,
+    pub lock_time: u32,
+    /// Only serialized by Overwinter and later transactions.
+    pub expiry_height: u32,
+    pub sprout: Option<JoinSplitData>,
+    pub sapling: Option<SaplingShieldedData>,
+    pub orchard: Option<OrchardShieldedData>,
+}
+
+impl Transaction {
+    pub fn from_bytes(data: &[u8]) -> Result<Transaction, TransactionError> {
+        let mut header = [0; 4];
+        header.copy_from_slice(data.get(..4).ok_or(TransactionError::UnexpectedEnd)?);
+        if u32::from_le_bytes(header) & OVERWINTERED_FLAG != 0 {
+            return Reader::new(data).finish(Reader::overwintered);
+        }
+        // A transaction without inputs reads like a segwit marker; fall back to the
+        // plain format when the segwit reading does not fit.
+        Reader::new(data)
+            .finish(|r| r.legacy(true))
+            .or_else(|err| Reader::new(data).finish(|r| r.legacy(false)).map_err(|_| err))
+    }
+
+    pub fn from_hex(s: &str) -> Result<Transaction, TransactionError> {
+        let data = hex::decode(s).map_err(|_| TransactionError::Hex)?;
+        Transaction::from_bytes(&data)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write(&mut out, true);
+        out
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    pub fn is_overwintered(&self) -> bool {
+        self.version_group_id.is_some()
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1 && self.inputs[0].prevout.is_null()
+    }
+
+    pub fn has_witness(&self) -> bool {
+        self.inputs.iter().any(|input| !input.witness.is_empty())
+    }
+
+    /// Txid in the hex form used by RPC calls.
+    ///
+    /// Computed as the ZIP 244 digest for v5 transactions and as the double SHA-256 of
+    /// the serialization without witnesses otherwise.
+    pub fn txid(&self) -> String {
+        reversed_hex(&self.txid_bytes())
+    }
+
+    /// Txid in internal byte order, as referenced by `OutPoint`.
+    pub fn txid_bytes(&self) -> [u8; 32] {
+        if self.is_v5() {
+            return self.v5_digest(b"ZcashTxHash_", TxDigests::txid);
+        }
+        let mut out = Vec::new();
+        self.write(&mut out, false);
+        sha256d(&out)
+    }
+
+    /// Bitcoin witness txid; equal to the txid for transactions without witnesses.
+    pub fn wtxid(&self) -> String {
+        reversed_hex(&sha256d(&self.to_bytes()))
+    }
+
+    /// ZIP 244 authorizing data commitment of a v5 transaction, as `authdigest` is
+    /// reported by zcashd.
+    pub fn auth_digest(&self) -> Option<String> {
+        if !self.is_v5() {
+            return None;
+        }
+        let digest = self.v5_digest(b"ZTxAuthHash_", TxDigests::auth);
+        Some(reversed_hex(&digest))
+    }
+
+    fn is_v5(&self) -> bool {
+        self.is_overwintered() && self.version >= 5
+    }
+
+    fn write(&self, out: &mut Vec<u8>, with_witness: bool) {
+        let version_group_id = match self.version_group_id {
+            Some(version_group_id) => version_group_id,
+            None => return self.write_legacy(out, with_witness),
+        };
+        out.extend_from_slice(&(self.version | OVERWINTERED_FLAG).to_le_bytes());
+        out.extend_from_slice(&version_group_id.to_le_bytes());
+        if self.version >= 5 {
+            out.extend_from_slice(&self.consensus_branch_id.to_le_bytes());
+            out.extend_from_slice(&self.lock_time.to_le_bytes());
+            out.extend_from_slice(&self.expiry_height.to_le_bytes());
+            write_vec(out, &self.inputs, TransparentInput::write);
+            write_vec(out, &self.outputs, TransparentOutput::write);
+            write_sapling_v5(out, self.sapling.as_ref());
+            write_orchard(out, self.orchard.as_ref());
+            return;
+        }
+
+        write_vec(out, &self.inputs, TransparentInput::write);
+        write_vec(out, &self.outputs, TransparentOutput::write);
+        out.extend_from_slice(&self.lock_time.to_le_bytes());
+        out.extend_from_slice(&self.expiry_height.to_le_bytes());
+        let sapling = self.sapling.as_ref().filter(|_| self.version >= 4);
+        if self.version >= 4 {
+            let value_balance = sapling.map_or(0, |bundle| bundle.value_balance.as_sat());
+            out.extend_from_slice(&value_balance.to_le_bytes());
+            write_vec(out, sapling.map_or(&[][..], |b| &b.spends), SpendDescription::write_v4);
+            write_vec(out, sapling.map_or(&[][..], |b| &b.outputs), OutputDescription::write_v4);
+        }
+        write_sprout(out, self.sprout.as_ref());
+        if let Some(bundle) = sapling.filter(|bundle| bundle.has_descriptions()) {
+            out.extend_from_slice(&bundle.binding_sig);
+        }
+    }
+
+    fn write_legacy(&self, out: &mut Vec<u8>, with_witness: bool) {
+        let segwit = with_witness && self.has_witness();
+        out.extend_from_slice(&self.version.to_le_bytes());
+        if segwit {
+            out.extend_from_slice(&[0x00, 0x01]);
+        }
+        write_vec(out, &self.inputs, TransparentInput::write);
+        write_vec(out, &self.outputs, TransparentOutput::write);
+        if segwit {
+            for input in &self.inputs {
+                write_vec(out, &input.witness, |item, out| write_bytes(out, item));
+            }
+        }
+        out.extend_from_slice(&self.lock_time.to_le_bytes());
+        if self.version >= 2 && self.sprout.is_some() {
+            write_sprout(out, self.sprout.as_ref());
+        }
+    }
+
+    fn v5_digest<F>(&self, prefix: &[u8; 12], digests: F) -> [u8; 32]
+    where
+        F: Fn(&Transaction) -> TxDigests,
+    {
+        let mut personal = [0; 16];
+        personal[..12].copy_from_slice(prefix);
+        personal[12..].copy_from_slice(&self.consensus_branch_id.to_le_bytes());
+        let mut state = blake2b(&personal);
+        for digest in &digests(self).0 {
+            state.update(digest);
+        }
+        to_array(state)
+    }
+}
+
+impl TransparentInput {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.prevout.write(out);
+        write_bytes(out, &self.script_sig);
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+    }
+}
+
+impl OutPoint {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.txid);
+        out.extend_from_slice(&self.vout.to_le_bytes());
+    }
+}
+
+impl TransparentOutput {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.value.as_sat().to_le_bytes());
+        write_bytes(out, &self.script_pubkey);
+    }
+}
+
+impl JoinSplitDescription {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.vpub_old.as_sat().to_le_bytes());
+        out.extend_from_slice(&self.vpub_new.as_sat().to_le_bytes());
+        out.extend_from_slice(&self.anchor);
+        for field in &[self.nullifiers, self.commitments] {
+            out.extend_from_slice(&field[0]);
+            out.extend_from_slice(&field[1]);
+        }
+        out.extend_from_slice(&self.ephemeral_key);
+        out.extend_from_slice(&self.random_seed);
+        out.extend_from_slice(&self.macs[0]);
+        out.extend_from_slice(&self.macs[1]);
+        out.extend_from_slice(&self.proof);
+        out.extend_from_slice(&self.ciphertexts[0]);
+        out.extend_from_slice(&self.ciphertexts[1]);
+    }
+}
+
+impl SpendDescription {
+    fn write_v4(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.cv);
+        out.extend_from_slice(&self.anchor);
+        out.extend_from_slice(&self.nullifier);
+        out.extend_from_slice(&self.rk);
+        out.extend_from_slice(&self.zkproof);
+        out.extend_from_slice(&self.spend_auth_sig);
+    }
+}
+
+impl OutputDescription {
+    fn write_v4(&self, out: &mut Vec<u8>) {
+        self.write_v5(out);
+        out.extend_from_slice(&self.zkproof);
+    }
+
+    fn write_v5(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.cv);
+        out.extend_from_slice(&self.cmu);
+        out.extend_from_slice(&self.ephemeral_key);
+        out.extend_from_slice(&self.enc_ciphertext);
+        out.extend_from_slice(&self.out_ciphertext);
+    }
+}
+
+impl SaplingShieldedData {
+    fn has_descriptions(&self) -> bool {
+        !self.spends.is_empty() || !self.outputs.is_empty()
+    }
+}
+
+fn write_sprout(out: &mut Vec<u8>, sprout: Option<&JoinSplitData>) {
+    let joinsplits = sprout.map_or(&[][..], |bundle| &bundle.joinsplits);
+    write_vec(out, joinsplits, JoinSplitDescription::write);
+    if let Some(bundle) = sprout.filter(|bundle| !bundle.joinsplits.is_empty()) {
+        out.extend_from_slice(&bundle.pubkey);
+        out.extend_from_slice(&bundle.sig);
+    }
+}
+
+fn write_sapling_v5(out: &mut Vec<u8>, sapling: Option<&SaplingShieldedData>) {
+    let bundle = match sapling.filter(|bundle| bundle.has_descriptions()) {
+        Some(bundle) => bundle,
+        None => return out.extend_from_slice(&[0x00, 0x00]),
+    };
+    write_vec(out, &bundle.spends, |spend, out| {
+        out.extend_from_slice(&spend.cv);
+        out.extend_from_slice(&spend.nullifier);
+        out.extend_from_slice(&spend.rk);
+    });
+    write_vec(out, &bundle.outputs, OutputDescription::write_v5);
+    out.extend_from_slice(&bundle.value_balance.as_sat().to_le_bytes());
+    if let Some(spend) = bundle.spends.first() {
+        out.extend_from_slice(&spend.anchor);
+    }
+    for spend in &bundle.spends {
+        out.extend_from_slice(&spend.zkproof);
+    }
+    for spend in &bundle.spends {
+        out.extend_from_slice(&spend.spend_auth_sig);
+    }
+    for output in &bundle.outputs {
+        out.extend_from_slice(&output.zkproof);
+    }
+    out.extend_from_slice(&bundle.binding_sig);
+}
+
+fn write_orchard(out: &mut Vec<u8>, orchard: Option<&OrchardShieldedData>) {
+    let bundle = match orchard.filter(|bundle| !bundle.actions.is_empty()) {
+        Some(bundle) => bundle,
+        None => return out.push(0x00),
+    };
+    write_vec(out, &bundle.actions, |action, out| {
+        out.extend_from_slice(&action.cv);
+        out.extend_from_slice(&action.nullifier);
+        out.extend_from_slice(&action.rk);
+        out.extend_from_slice(&action.cmx);
+        out.extend_from_slice(&action.ephemeral_key);
+        out.extend_from_slice(&action.enc_ciphertext);
+        out.extend_from_slice(&action.out_ciphertext);
+    });
+    out.push(bundle.flags);
+    out.extend_from_slice(&bundle.value_balance.as_sat().to_le_bytes());
+    out.extend_from_slice(&bundle.anchor);
+    write_bytes(out, &bundle.proof);
+    for action in &bundle.actions {
+        out.extend_from_slice(&action.spend_auth_sig);
+    }
+    out.extend_from_slice(&bundle.binding_sig);
+}
+
+fn write_vec<T, F>(out: &mut Vec<u8>, items: &[T], mut write: F)
+where
+    F: FnMut(&T, &mut Vec<u8>),
+{
+    write_compact_size(out, items.len() as u64);
+    for item in items {
+        write(item, out);
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_compact_size(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn reversed_hex(bytes: &[u8; 32]) -> String {
+    let mut reversed = *bytes;
+    reversed.reverse();
+    hex::encode(reversed)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    fn finish<F>(mut self, read: F) -> Result<Transaction, TransactionError>
+    where
+        F: FnOnce(&mut Reader<'a>) -> Result<Transaction, TransactionError>,
+    {
+        let transaction = read(&mut self)?;
+        if !self.data.is_empty() {
+            return Err(TransactionError::TrailingData(self.data.len()));
+        }
+        Ok(transaction)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], TransactionError> {
+        if self.data.len() < len {
+            return Err(TransactionError::UnexpectedEnd);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], TransactionError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, TransactionError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, TransactionError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn amount(&mut self) -> Result<Amount, TransactionError> {
+        self.array().map(i64::from_le_bytes).map(Amount::from_sat)
+    }
+
+    fn compact_size(&mut self) -> Result<usize, TransactionError> {
+        if self.data.is_empty() {
+            return Err(TransactionError::UnexpectedEnd);
+        }
+        match read_compact_size(&mut self.data) {
+            Some(size) if size <= MAX_SIZE as u64 => Ok(size as usize),
+            _ => Err(TransactionError::InvalidLength),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, TransactionError> {
+        let len = self.compact_size()?;
+        self.take(len).map(<[u8]>::to_vec)
+    }
+
+    fn vec<T, F>(&mut self, mut read: F) -> Result<Vec<T>, TransactionError>
+    where
+        F: FnMut(&mut Reader<'a>) -> Result<T, TransactionError>,
+    {
+        let count = self.compact_size()?;
+        (0..count).map(|_| read(self)).collect()
+    }
+
+    fn legacy(&mut self, allow_witness: bool) -> Result<Transaction, TransactionError> {
+        let version = self.u32()?;
+        let mut inputs = self.vec(Reader::input)?;
+        let mut segwit = false;
+        let outputs = if inputs.is_empty() && allow_witness {
+            match self.u8()? {
+                0x00 => Vec::new(),
+                0x01 => {
+                    segwit = true;
+                    inputs = self.vec(Reader::input)?;
+                    self.vec(Reader::output)?
+                }
+                _ => return Err(TransactionError::InvalidWitness),
+            }
+        } else {
+            self.vec(Reader::output)?
+        };
+        if segwit {
+            for input in &mut inputs {
+                input.witness = self.vec(Reader::bytes)?;
+            }
+            if inputs.iter().all(|input| input.witness.is_empty()) {
+                return Err(TransactionError::InvalidWitness);
+            }
+        }
+        let lock_time = self.u32()?;
+        let sprout = if version >= 2 && !segwit && !self.data.is_empty() {
+            Some(self.sprout(BCTV14_PROOF_LEN)?.unwrap_or_else(JoinSplitData::empty))
+        } else {
+            None
+        };
+        Ok(Transaction {
+            version,
+            version_group_id: None,
+            consensus_branch_id: 0,
+            inputs,
+            outputs,
+            lock_time,
+            expiry_height: 0,
+            sprout,
+            sapling: None,
+            orchard: None,
+        })
+    }
+
+    fn overwintered(&mut self) -> Result<Transaction, TransactionError> {
+        let version = self.u32()? & !OVERWINTERED_FLAG;
+        let version_group_id = self.u32()?;
+        let expected = match version {
+            3 => OVERWINTER_VERSION_GROUP_ID,
+            4 => SAPLING_VERSION_GROUP_ID,
+            5 => NU5_VERSION_GROUP_ID,
+            _ => return Err(TransactionError::UnsupportedVersion(version)),
+        };
+        if version_group_id != expected {
+            return Err(TransactionError::UnknownVersionGroup(version_group_id));
+        }
+        let mut transaction = Transaction {
+            version,
+            version_group_id: Some(version_group_id),
+            consensus_branch_id: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            lock_time: 0,
+            expiry_height: 0,
+            sprout: None,
+            sapling: None,
+            orchard: None,
+        };
+        if version == 5 {
+            transaction.consensus_branch_id = self.u32()?;
+            transaction.lock_time = self.u32()?;
+            transaction.expiry_height = self.u32()?;
+            transaction.inputs = self.vec(Reader::input)?;
+            transaction.outputs = self.vec(Reader::output)?;
+            transaction.sapling = self.sapling_v5()?;
+            transaction.orchard = self.orchard()?;
+            return Ok(transaction);
+        }
+
+        transaction.inputs = self.vec(Reader::input)?;
+        transaction.outputs = self.vec(Reader::output)?;
+        transaction.lock_time = self.u32()?;
+        transaction.expiry_height = self.u32()?;
+        let mut sapling = None;
+        if version == 4 {
+            let value_balance = self.amount()?;
+            let spends = self.vec(Reader::sapling_spend_v4)?;
+            let outputs = self.vec(Reader::sapling_output_v4)?;
+            if spends.is_empty() && outputs.is_empty() {
+                if value_balance != Amount::ZERO {
+                    return Err(TransactionError::UnexpectedValueBalance);
+                }
+            } else {
+                sapling = Some(SaplingShieldedData {
+                    spends,
+                    outputs,
+                    value_balance,
+                    binding_sig: [0; 64],
+                });
+            }
+        }
+        let proof_len = if version == 3 {
+            BCTV14_PROOF_LEN
+        } else {
+            GROTH_PROOF_LEN
+        };
+        transaction.sprout = self.sprout(proof_len)?;
+        if let Some(ref mut bundle) = sapling {
+            bundle.binding_sig = self.array()?;
+        }
+        transaction.sapling = sapling;
+        Ok(transaction)
+    }
+
+    fn input(&mut self) -> Result<TransparentInput, TransactionError> {
+        Ok(TransparentInput {
+            prevout: OutPoint {
+                txid: self.array()?,
+                vout: self.u32()?,
+            },
+            script_sig: self.bytes()?,
+            sequence: self.u32()?,
+            witness: Vec::new(),
+        })
+    }
+
+    fn output(&mut self) -> Result<TransparentOutput, TransactionError> {
+        Ok(TransparentOutput {
+            value: self.amount()?,
+            script_pubkey: self.bytes()?,
+        })
+    }
+
+    // Returns `None` when the transaction has no JoinSplits.
+    fn sprout(&mut self, proof_len: usize) -> Result<Option<JoinSplitData>, TransactionError> {
+        let joinsplits = self.vec(|r| r.joinsplit(proof_len))?;
+        if joinsplits.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(JoinSplitData {
+            joinsplits,
+            pubkey: self.array()?,
+            sig: self.array()?,
+        }))
+    }
+
+    fn joinsplit(&mut self, proof_len: usize) -> Result<JoinSplitDescription, TransactionError> {
+        Ok(JoinSplitDescription {
+            vpub_old: self.amount()?,
+            vpub_new: self.amount()?,
+            anchor: self.array()?,
+            nullifiers: [self.array()?, self.array()?],
+            commitments: [self.array()?, self.array()?],
+            ephemeral_key: self.array()?,
+            random_seed: self.array()?,
+            macs: [self.array()?, self.array()?],
+            proof: self.take(proof_len)?.to_vec(),
+            ciphertexts: [self.array()?, self.array()?],
+        })
+    }
+
+    fn sapling_spend_v4(&mut self) -> Result<SpendDescription, TransactionError> {
+        Ok(SpendDescription {
+            cv: self.array()?,
+            anchor: self.array()?,
+            nullifier: self.array()?,
+            rk: self.array()?,
+            zkproof: self.array()?,
+            spend_auth_sig: self.array()?,
+        })
+    }
+
+    fn sapling_output_v4(&mut self) -> Result<OutputDescription, TransactionError> {
+        let mut output = self.sapling_output_v5()?;
+        output.zkproof = self.array()?;
+        Ok(output)
+    }
+
+    fn sapling_output_v5(&mut self) -> Result<OutputDescription, TransactionError> {
+        Ok(OutputDescription {
+            cv: self.array()?,
+            cmu: self.array()?,
+            ephemeral_key: self.array()?,
+            enc_ciphertext: self.array()?,
+            out_ciphertext: self.array()?,
+            zkproof: [0; GROTH_PROOF_LEN],
+        })
+    }
+
+    fn sapling_v5(&mut self) -> Result<Option<SaplingShieldedData>, TransactionError> {
+        let mut spends = self.vec(|r| {
+            Ok(SpendDescription {
+                cv: r.array()?,
+                anchor: [0; 32],
+                nullifier: r.array()?,
+                rk: r.array()?,
+                zkproof: [0; GROTH_PROOF_LEN],
+                spend_auth_sig: [0; 64],
+            })
+        })?;
+        let mut outputs = self.vec(Reader::sapling_output_v5)?;
+        if spends.is_empty() && outputs.is_empty() {
+            return Ok(None);
+        }
+        let value_balance = self.amount()?;
+        if !spends.is_empty() {
+            let anchor = self.array()?;
+            for spend in &mut spends {
+                spend.anchor = anchor;
+            }
+        }
+        for spend in &mut spends {
+            spend.zkproof = self.array()?;
+        }
+        for spend in &mut spends {
+            spend.spend_auth_sig = self.array()?;
+        }
+        for output in &mut outputs {
+            output.zkproof = self.array()?;
+        }
+        Ok(Some(SaplingShieldedData {
+            spends,
+            outputs,
+            value_balance,
+            binding_sig: self.array()?,
+        }))
+    }
+
+    fn orchard(&mut self) -> Result<Option<OrchardShieldedData>, TransactionError> {
+        let mut actions = self.vec(|r| {
+            Ok(ActionDescription {
+                cv: r.array()?,
+                nullifier: r.array()?,
+                rk: r.array()?,
+                cmx: r.array()?,
+                ephemeral_key: r.array()?,
+                enc_ciphertext: r.array()?,
+                out_ciphertext: r.array()?,
+                spend_auth_sig: [0; 64],
+            })
+        })?;
+        if actions.is_empty() {
+            return Ok(None);
+        }
+        let flags = self.u8()?;
+        let value_balance = self.amount()?;
+        let anchor = self.array()?;
+        let proof = self.bytes()?;
+        for action in &mut actions {
+            action.spend_auth_sig = self.array()?;
+        }
+        Ok(Some(OrchardShieldedData {
+            actions,
+            flags,
+            value_balance,
+            anchor,
+            proof,
+            binding_sig: self.array()?,
+        }))
+    }
+}
+
+impl JoinSplitData {
+    fn empty() -> JoinSplitData {
+        JoinSplitData {
+            joinsplits: Vec::new(),
+            pubkey: [0; 32],
+            sig: [0; 64],
+        }
+    }
+}
+
+// Top-level digests of a v5 transaction, hashed together under a branch-specific
+// personalization as specified by ZIP 244.
+struct TxDigests(Vec<[u8; 32]>);
+
+impl TxDigests {
+    fn txid(tx: &Transaction) -> TxDigests {
+        let mut header = blake2b(b"ZTxIdHeadersHash");
+        header.update(&(tx.version | OVERWINTERED_FLAG).to_le_bytes());
+        header.update(&tx.version_group_id.unwrap_or_default().to_le_bytes());
+        header.update(&tx.consensus_branch_id.to_le_bytes());
+        header.update(&tx.lock_time.to_le_bytes());
+        header.update(&tx.expiry_height.to_le_bytes());
+
+        let mut transparent = blake2b(b"ZTxIdTranspaHash");
+        if !tx.inputs.is_empty() || !tx.outputs.is_empty() {
+            let mut prevouts = blake2b(b"ZTxIdPrevoutHash");
+            let mut sequences = blake2b(b"ZTxIdSequencHash");
+            let mut outputs = blake2b(b"ZTxIdOutputsHash");
+            let mut data = Vec::new();
+            for input in &tx.inputs {
+                data.clear();
+                input.prevout.write(&mut data);
+                prevouts.update(&data);
+                sequences.update(&input.sequence.to_le_bytes());
+            }
+            for output in &tx.outputs {
+                data.clear();
+                output.write(&mut data);
+                outputs.update(&data);
+            }
+            transparent.update(&to_array(prevouts));
+            transparent.update(&to_array(sequences));
+            transparent.update(&to_array(outputs));
+        }
+
+        let mut sapling = blake2b(b"ZTxIdSaplingHash");
+        if let Some(bundle) = tx.sapling.as_ref().filter(|b| b.has_descriptions()) {
+            let mut spends = blake2b(b"ZTxIdSSpendsHash");
+            if !bundle.spends.is_empty() {
+                let mut compact = blake2b(b"ZTxIdSSpendCHash");
+                let mut noncompact = blake2b(b"ZTxIdSSpendNHash");
+                for spend in &bundle.spends {
+                    compact.update(&spend.nullifier);
+                    noncompact.update(&spend.cv);
+                    noncompact.update(&spend.anchor);
+                    noncompact.update(&spend.rk);
+                }
+                spends.update(&to_array(compact));
+                spends.update(&to_array(noncompact));
+            }
+            let mut outputs = blake2b(b"ZTxIdSOutputHash");
+            if !bundle.outputs.is_empty() {
+                let mut compact = blake2b(b"ZTxIdSOutC__Hash");
+                let mut memos = blake2b(b"ZTxIdSOutM__Hash");
+                let mut noncompact = blake2b(b"ZTxIdSOutN__Hash");
+                for output in &bundle.outputs {
+                    compact.update(&output.cmu);
+                    compact.update(&output.ephemeral_key);
+                    compact.update(&output.enc_ciphertext[..52]);
+                    memos.update(&output.enc_ciphertext[52..564]);
+                    noncompact.update(&output.cv);
+                    noncompact.update(&output.enc_ciphertext[564..]);
+                    noncompact.update(&output.out_ciphertext);
+                }
+                outputs.update(&to_array(compact));
+                outputs.update(&to_array(memos));
+                outputs.update(&to_array(noncompact));
+            }
+            sapling.update(&to_array(spends));
+            sapling.update(&to_array(outputs));
+            sapling.update(&bundle.value_balance.as_sat().to_le_bytes());
+        }
+
+        let mut orchard = blake2b(b"ZTxIdOrchardHash");
+        if let Some(bundle) = tx.orchard.as_ref().filter(|b| !b.actions.is_empty()) {
+            let mut compact = blake2b(b"ZTxIdOrcActCHash");
+            let mut memos = blake2b(b"ZTxIdOrcActMHash");
+            let mut noncompact = blake2b(b"ZTxIdOrcActNHash");
+            for action in &bundle.actions {
+                compact.update(&action.nullifier);
+                compact.update(&action.cmx);
+                compact.update(&action.ephemeral_key);
+                compact.update(&action.enc_ciphertext[..52]);
+                memos.update(&action.enc_ciphertext[52..564]);
+                noncompact.update(&action.cv);
+                noncompact.update(&action.rk);
+                noncompact.update(&action.enc_ciphertext[564..]);
+                noncompact.update(&action.out_ciphertext);
+            }
+            orchard.update(&to_array(compact));
+            orchard.update(&to_array(memos));
+            orchard.update(&to_array(noncompact));
+            orchard.update(&[bundle.flags]);
+            orchard.update(&bundle.value_balance.as_sat().to_le_bytes());
+            orchard.update(&bundle.anchor);
+        }
+
+        TxDigests(vec![
+            to_array(header),
+            to_array(transparent),
+            to_array(sapling),
+            to_array(orchard),
+        ])
+    }
+
+    fn auth(tx: &Transaction) -> TxDigests {
+        let mut transparent = blake2b(b"ZTxAuthTransHash");
+        let mut data = Vec::new();
+        for input in &tx.inputs {
+            write_bytes(&mut data, &input.script_sig);
+        }
+        transparent.update(&data);
+
+        let mut sapling = blake2b(b"ZTxAuthSapliHash");
+        if let Some(bundle) = tx.sapling.as_ref().filter(|b| b.has_descriptions()) {
+            for spend in &bundle.spends {
+                sapling.update(&spend.zkproof);
+            }
+            for spend in &bundle.spends {
+                sapling.update(&spend.spend_auth_sig);
+            }
+            for output in &bundle.outputs {
+                sapling.update(&output.zkproof);
+            }
+            sapling.update(&bundle.binding_sig);
+        }
+
+        let mut orchard = blake2b(b"ZTxAuthOrchaHash");
+        if let Some(bundle) = tx.orchard.as_ref().filter(|b| !b.actions.is_empty()) {
+            orchard.update(&bundle.proof);
+            for action in &bundle.actions {
+                orchard.update(&action.spend_auth_sig);
+            }
+            orchard.update(&bundle.binding_sig);
+        }
+
+        TxDigests(vec![
+            to_array(transparent),
+            to_array(sapling),
+            to_array(orchard),
+        ])
+    }
+}
+
+fn blake2b(personal: &[u8; 16]) -> Blake2bState {
+    Blake2bParams::new()
+        .hash_length(32)
+        .personal(personal)
+        .to_state()
+}
+
+fn to_array(state: Blake2bState) -> [u8; 32] {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(state.finalize().as_bytes());
+    hash
+}
+
+impl FromStr for Transaction {
+    type Err = TransactionError;
+
+    fn from_str(s: &str) -> Result<Transaction, TransactionError> {
+        Transaction::from_hex(s)
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl Serialize for Transaction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Transaction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Transaction, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Transaction, TransactionError, OVERWINTER_VERSION_GROUP_ID,
+                SAPLING_VERSION_GROUP_ID};
+
+    // Bitcoin genesis coinbase.
+    const GENESIS: (&str, &str) = (
+        "01000000010000000000000000000000000000000000000000000000000000000000000000ffffff\
+        ff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c\
+        6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ff\
+        ffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a679\
+        62e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac\
+        00000000",
+        "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+    );
+
+    // Bitcoin transaction without inputs, whose output count reads like a segwit flag.
+    const NO_INPUTS: (&str, &str) = (
+        "0100000000010000000000000000016a00000000",
+        "4b3fa2307a7d80ba0ba0b90ed9d26412a5c315f9ed0f0861619cf085ae308d31",
+    );
+
+    // Bitcoin segwit transaction, with its txid and wtxid.
+    const SEGWIT: (&str, &str, &str) = (
+        "02000000000101d434c7d7976cc633f94042e59fbc5813e313bb0c686b6a0168d26a9a48a2599301\
+        00000000fdffffff01b882010000000000160014a423de7cfa5c1c2dbc65a04006b1b0713bdd698d\
+        024230a543997d84f12798350c09bdef2cdb171bf41ed3e4a5f808af2feb0c56263009a543997d84\
+        f12798350c09bdef2cdb171bf41ed3e4a5f808af2feb0c562630090121020017dea7770f7ecff7ab\
+        3c20506546129e96bdeba2f544bb8e5414eb7978612210eb0900",
+        "ec5f9b00848e4dedf2e03bf4fc8802b708c8e0dd270bdfad3508826fce4e21cf",
+        "34e45e54da76596e122f42c2f246003f3ef2ab62ff52e1fc0e6e001bb5216c09",
+    );
+
+    // Pre-Overwinter Zcash transaction with a BCTV14 JoinSplit.
+    const SPROUT_V2: (&str, &str) = (
+        "020000000213e56c772f2c3b860ea5b03a8854bc6e6590d63cc0ea54f10b73ba241bf74b635551a2\
+        aa06656aac525163368b26d70a737f267685998a3f7d2637914909c746495d24c498635ef97ac66a\
+        400894c09f73488e076a53655251655205f91ad7007965c299010000000000000000000000000000\
+        00007e89ee0962f58c051d11d055fce204a562de68088a1b2648b8174cbcfc8b5b5cd077115afde1\
+        8405054e5da9a04310342c5d3b526e0b02c5ca1722badeee23d145e8eb2213fc4af1e450e4d5217c\
+        6617008c78f4fb1112f4028a704fc5a9382c6b03e7d8085e906cf84ca2c1207c87a2bce2080a9891\
+        668d69b044beced6cda32c229c9117917aa07ddffcd377395cba616d63c0b69c01fcc45391fd5b87\
+        63fb96d7ca333a12de3cefa91c6c98f9473b8e104a71293e46374705baf65fa41384ba5c8e0c88a3\
+        eb07e0be34daddfabb7b65543b5f39cb2023d46789eb7d989af779e5b8d28385a85b0da2abe07f0c\
+        2bb4255fcea03188527a307d409159e90166fac6a070ba05b3e4dbfd3a2bfcc9ee6ed016c0f665be\
+        8133b7dc1d86044db0f90340fb0e9f8bc2e4db5382a8b4f815b4e8434ad0dfbc51a5e9b145e1596c\
+        bf467003e05dfdafbb0cf3ddee28d76a82428e8aba4364e84bac379298df2932e69bb5d00b516efc\
+        33ae6cc3947ceb09ed371667212a831b5485eafce8488188ea4e27d0cdf7ddd348abff777f4a13bb\
+        c716b6a5944ee727965690e209b49eb962c039975f039ed5c6e4c400d887759433d3ad716da0cb44\
+        6113c7727a64b58c3f8a0f81189f02005233a81366aee73cec85228ebcfd5ee3c3fb44db76ba243f\
+        2842b7b5fc746a031b0bc4bd4fc9fd833565ea852b92b224f6990318ad8c7d9437e20e2a1f20e818\
+        03057c5abaaa2e5c15b94945cd424c28a5fa385dadfe4907b274d842707db3697a02e6c8f542e5ec\
+        c07fe47350d1014670212efe81fb7c73e8450df814ef6232f7490f63ccf07480f884a66eaffc28fe\
+        a448d7b401cdae10e7c0c7f9a7b15331969fc8cb36396773de191931c750f6ce5caaf29768ebb27d\
+        acc738056a8125b4772bf87ae10a8a309b9bd655043cfc3159494368c5ab8cadb7f671e9626bd263\
+        e31181a604b506a03b439a7ffe4355892477e2bdf338c62c3922f7d3c9a56c7103d911948a84b5ae\
+        2dbb16a3761add053a0f967e6b5bc94211b6547153267c6ee1cad0d974a71088583735e4f63d3315\
+        6dadd54c2faf89114a127b97b94cc2a22ef303f459d04fc0b53ace5918d47ff33a558bd71a75f355\
+        fbd06bbccf4e02c3c0a4b63d0cc949801d63a64cb2d32373b2c7b274ab2db4682142c8b21d84c481\
+        f5ef21e4b5e3603451bf94774d0ef47f63fa6abb78d21c193cbe65b695fe67423c1e2d312e2776fa\
+        24ece84683e74876c55ea0369e4ea0e86494e00dde236a1689731f0a5d8203afde5c423640b81e4f\
+        631c981c11a2e1d184c67c528df92d53aec44a40a4ea2a131b4733cfe45c6b0012c3e9e20975baae\
+        cb0232df880bd7d1de13e1349462ec8d5df3e780ffa72eba8a8df7fcf398ec230513ca9d6123f8b9\
+        d8178560daf975111955a2bca3423eeefc527be3a8543eb90a5ec02f35a7c64b7dd59a72da007463\
+        4e01d2abf3637add77c7350f12b011b294168ec75576e47d169e3938bf6ae2aa8ff7cfba7cacb1f9\
+        2b6e4c2497bffa9f17cad242fa9c3179c1a3aa81f7361649572c715c25a1f6cd5ace82c00ab2342b\
+        9c3cb4fffdda160ca5ab9e9baf2139ef9afbe1b1f309462afce462a79bb9698e22c957c590a753a7\
+        6b87e009121e06f6a1bf62a08bf435d92e2fffe86e2a9cbba9133a68e4aebf33c38436f2545fc2d5\
+        2832d165af415b244adc5f57377deedf460aa3beb43419c6b082e835ce84ca13b6908a8813c021de\
+        9fa9a44e4c18dcb3d21faabdb41931b2fd497644dc3a1507fa5ac7c76beebbdbd1d49299a55bd499\
+        27e9d7f4884e6ed3fd5e4b7cb835b83308964e3c46873fd613317b91d29236ea90e365d162cc051c\
+        846d242176daf6d28618ae31fbaae999a93f175c6938e631a081f2c1f3fd782549d3f3245759606d\
+        9f92d5548acfeadbaf9caa6b93dc08828d74f6d5fdd83331f0969145955297e69f00fd2987f2da2b\
+        94b995fecbe622a735ef7f1207f671629489202bea0b475e51681aa16778b39bd923c98dc6ff8373\
+        c79bb17030417bc200c8f0b855acfec179f7674cec2721a10fca693d83cfe5b8cdcc18f81ad617fa\
+        26f0dfb83655b8a29a7f834232425e8c474588f18dd326aa396c3e4775e00205fc9e45f7b7d2e6d5\
+        5dcb90e23ff6b508459aa699bfcbd56f10997764d087408986e73d6e284fea9a23c39311782f86ca\
+        bff9455e4cf699e5f5d4bc0b3905a4e3bd01c54df8643443be0f889032ea325bf07107fd41d673ee\
+        bae6fa637b70cc0ed3f00958dfb8dcf00e85a1d0a6a8908140c2f434c2e260efb0bca2003504c999\
+        93a9e1c0ff9cefe6a665d791428690e47ef8c131a8e9bfb4c3080235032d731b0d3841225f1c11e2\
+        c28ee84d35f9226100565972eb269d278ef64979bf6515ed4a6840b0883a9e6ef64a0efcae1cf21d\
+        fe74854e84c2749fac03825275c9b6302184c72df4c4bb2862e4e8a7d9a4a282866f9a7b2cfc9a56\
+        313da0c47a34b7b9cda3ace8185f07df36e448a76aa477f224d87a074f43af5d5f79b3ab1128f081\
+        91447fa646bfdde5b51e233ca6155d101585bc2c40158ac2106e66a26e46423370636876b434a74f\
+        8ce8060050b082a79b61bb5d344eb5a1158326ced9a9d9f54fb2fe8f9f05cd111ee46c4710f6f63a\
+        62694557",
+        "810d24c5a1057393605e3c2025b977050e656ab8320323e2b7a2a5b444dfc905",
+    );
+
+    // Transparent Overwinter transaction from the zcashd sighash vectors.
+    const OVERWINTER_V3: (&str, &str) = (
+        "030000807082c4030187daa731f570a7a4060af0ce700d31bca7e74b3e3ba3d0e8a6392a062b8e86\
+        d9d7d00b21026553062e06b1013011ff08f083050009636a52635163006aac9abcef2a9908731900",
+        "7f8e5b5821c653f370cebb6beeea779d0ab86278432ca9af2df0ffa7f6a5416c",
+    );
+
+    // Overwinter transaction with a JoinSplit from the zcashd sighash vectors.
+    const OVERWINTER_V3_SPROUT: (&str, &str) = (
+        "030000807082c4030213e56c772f2c3b860ea5b03a8854bc6e6590d63cc0ea54f10b73ba241bf74b\
+        635551a2aa06656aac525163368b26d70a737f267685998a3f7d2637914909c746495d24c498635e\
+        f97ac66a400894c09f73488e076a53655251655205f91ad7007965c299361d600d01000000000000\
+        000000000000000000007e89ee0962f58c051d11d055fce204a562de68088a1b2648b8174cbcfc8b\
+        5b5cd077115afde18405054e5da9a04310342c5d3b526e0b02c5ca1722badeee23d145e8eb2213fc\
+        4af1e450e4d5217c6617008c78f4fb1112f4028a704fc5a9382c6b03e7d8085e906cf84ca2c1207c\
+        87a2bce2080a9891668d69b044beced6cda32c229c9117917aa07ddffcd377395cba616d63c0b69c\
+        01fcc45391fd5b8763fb96d7ca333a12de3cefa91c6c98f9473b8e104a71293e46374705baf65fa4\
+        1384ba5c8e0c88a3eb07e0be34daddfabb7b65543b5f39cb2023d46789eb7d989af779e5b8d28385\
+        a85b0da2abe07f0c2bb4255fcea03188527a307d409159e90166fac6a070ba05b3e4dbfd3a2bfcc9\
+        ee6ed016c0f665be8133b7dc1d86044db0f90340fb0e9f8bc2e4db5382a8b4f815b4e8434ad0dfbc\
+        51a5e9b145e1596cbf467003e05dfdafbb0cf3ddee28d76a82428e8aba4364e84bac379298df2932\
+        e69bb5d00b516efc33ae6cc3947ceb09ed371667212a831b5485eafce8488188ea4e27d0cdf7ddd3\
+        48abff777f4a13bbc716b6a5944ee727965690e209b49eb962c039975f039ed5c6e4c400d8877594\
+        33d3ad716da0cb446113c7727a64b58c3f8a0f81189f02005233a81366aee73cec85228ebcfd5ee3\
+        c3fb44db76ba243f2842b7b5fc746a031b0bc4bd4fc9fd833565ea852b92b224f6990318ad8c7d94\
+        37e20e2a1f20e81803057c5abaaa2e5c15b94945cd424c28a5fa385dadfe4907b274d842707db369\
+        7a02e6c8f542e5ecc07fe47350d1014670212efe81fb7c73e8450df814ef6232f7490f63ccf07480\
+        f884a66eaffc28fea448d7b401cdae10e7c0c7f9a7b15331969fc8cb36396773de191931c750f6ce\
+        5caaf29768ebb27dacc738056a8125b4772bf87ae10a8a309b9bd655043cfc3159494368c5ab8cad\
+        b7f671e9626bd263e31181a604b506a03b439a7ffe4355892477e2bdf338c62c3922f7d3c9a56c71\
+        03d911948a84b5ae2dbb16a3761add053a0f967e6b5bc94211b6547153267c6ee1cad0d974a71088\
+        583735e4f63d33156dadd54c2faf89114a127b97b94cc2a22ef303f459d04fc0b53ace5918d47ff3\
+        3a558bd71a75f355fbd06bbccf4e02c3c0a4b63d0cc949801d63a64cb2d32373b2c7b274ab2db468\
+        2142c8b21d84c481f5ef21e4b5e3603451bf94774d0ef47f63fa6abb78d21c193cbe65b695fe6742\
+        3c1e2d312e2776fa24ece84683e74876c55ea0369e4ea0e86494e00dde236a1689731f0a5d8203af\
+        de5c423640b81e4f631c981c11a2e1d184c67c528df92d53aec44a40a4ea2a131b4733cfe45c6b00\
+        12c3e9e20975baaecb0232df880bd7d1de13e1349462ec8d5df3e780ffa72eba8a8df7fcf398ec23\
+        0513ca9d6123f8b9d8178560daf975111955a2bca3423eeefc527be3a8543eb90a5ec02f35a7c64b\
+        7dd59a72da0074634e01d2abf3637add77c7350f12b011b294168ec75576e47d169e3938bf6ae2aa\
+        8ff7cfba7cacb1f92b6e4c2497bffa9f17cad242fa9c3179c1a3aa81f7361649572c715c25a1f6cd\
+        5ace82c00ab2342b9c3cb4fffdda160ca5ab9e9baf2139ef9afbe1b1f309462afce462a79bb9698e\
+        22c957c590a753a76b87e009121e06f6a1bf62a08bf435d92e2fffe86e2a9cbba9133a68e4aebf33\
+        c38436f2545fc2d52832d165af415b244adc5f57377deedf460aa3beb43419c6b082e835ce84ca13\
+        b6908a8813c021de9fa9a44e4c18dcb3d21faabdb41931b2fd497644dc3a1507fa5ac7c76beebbdb\
+        d1d49299a55bd49927e9d7f4884e6ed3fd5e4b7cb835b83308964e3c46873fd613317b91d29236ea\
+        90e365d162cc051c846d242176daf6d28618ae31fbaae999a93f175c6938e631a081f2c1f3fd7825\
+        49d3f3245759606d9f92d5548acfeadbaf9caa6b93dc08828d74f6d5fdd83331f0969145955297e6\
+        9f00fd2987f2da2b94b995fecbe622a735ef7f1207f671629489202bea0b475e51681aa16778b39b\
+        d923c98dc6ff8373c79bb17030417bc200c8f0b855acfec179f7674cec2721a10fca693d83cfe5b8\
+        cdcc18f81ad617fa26f0dfb83655b8a29a7f834232425e8c474588f18dd326aa396c3e4775e00205\
+        fc9e45f7b7d2e6d55dcb90e23ff6b508459aa699bfcbd56f10997764d087408986e73d6e284fea9a\
+        23c39311782f86cabff9455e4cf699e5f5d4bc0b3905a4e3bd01c54df8643443be0f889032ea325b\
+        f07107fd41d673eebae6fa637b70cc0ed3f00958dfb8dcf00e85a1d0a6a8908140c2f434c2e260ef\
+        b0bca2003504c99993a9e1c0ff9cefe6a665d791428690e47ef8c131a8e9bfb4c3080235032d731b\
+        0d3841225f1c11e2c28ee84d35f9226100565972eb269d278ef64979bf6515ed4a6840b0883a9e6e\
+        f64a0efcae1cf21dfe74854e84c2749fac03825275c9b6302184c72df4c4bb2862e4e8a7d9a4a282\
+        866f9a7b2cfc9a56313da0c47a34b7b9cda3ace8185f07df36e448a76aa477f224d87a074f43af5d\
+        5f79b3ab1128f08191447fa646bfdde5b51e233ca6155d101585bc2c40158ac2106e66a26e464233\
+        70636876b434a74f8ce8060050b082a79b61bb5d344eb5a1158326ced9a9d9f54fb2fe8f9f05cd11\
+        1ee46c4710f6f63a62694557",
+        "7a271efb284eed3ce93ffd2150197a28a97dae7b952f39b29452d028f2e9cb57",
+    );
+
+    // Sapling transaction with two spends and an output from the zcashd sighash vectors.
+    const SAPLING_V4: (&str, &str) = (
+        "0400008085202f8902dcf75876dca609f9d28471f997fa11f99d423f9cf1734be8a5ff997d451eb3\
+        cf4b3dfdd906acac6352636adc17a836b12b43befc0be0a1bd369772338078b4ff7d8e2d979a3441\
+        e1c8f5afe47b1e7da56cf006020053110c05cf00fda3e6cce36069041faffd2f77ff060002ef12c3\
+        67f21dea65c6eaafb8af58428f6c548e50170f9e6fcddfe751e0b68012cb59dd4627efc3ea75dcd1\
+        5c8e0c3b8d8d7d6b2331c8e480166b5aa7485c9f0f83e19bc30e6403828cdb652a556b1204093140\
+        2aa6ac34fc19fdc06e2e7787f558d142d906eadb7590c94136da6a063514d6a25f7b37d7664f9b97\
+        09433e6e702118a4ab9e7a7a3e6259129937d29d0db26070523e8b0643130abefe943b401298ae01\
+        a3ab00abbc60d7db933c7f07a8bf0f7ce1660bccb45e042b451b935002cece27f36aba5647ac28d8\
+        186cdd1fb95dc135d48992f68da12ad61ac756680dd7f8d0774abd6cfda2f032af3be139a633d673\
+        3c75d1aba89018c8572b99cd30c537067941df1c4bc1fd570f7b4ddc97518623e3ae4a87bdb966c9\
+        4d861e80de88c292aee9387194e256c6700752301c73fc9565a40480d8126e9d085879e24b16e9c4\
+        85d8f0d618ca0dd121b51a7cab230c5b45672bdb8ea3a040f7aaa098ba26025d2eab7948693dd5f6\
+        d3096501e9e07125d7eb293b3abad57fd5f011647002d626ae88dc61e647ff468dfa7a0307727879\
+        3275f195a97530289178516180c5ff9993536bda1504ba8bb4891988c1334f31fb276a038aa8e967\
+        cb62a4921beb22b208b064581847b2f64ca64837007216de6ecaffeb4b69e63347f84abcad8f2e75\
+        7d5861ce77ee46513da7416837dcb23d33ea72af23d0ad8c9307d0b5858da95b77fff9027b8859e1\
+        1dcbd598350eee50939481708ea708eb9f664388b9c64d6af0f96690342400348e929e07460253f3\
+        8390f87bd6c05308c3bde25228e0fa0880b08ef34a5a9cc0ea0a67ca65b6ffd005572909f1c42dd7\
+        45eeee9dd6b4439c9f3f98a118fe16698e9ceff558f16066975fe39583e9b5853b1311391580019f\
+        e55d59d1c828d3feb6a3b9ce92d089ae4b408e23d6a437d4989b519b7a9eb08ae6d448a7a16e8aed\
+        26a2ecd0cad80844fd0650d8c4e4d2af90656748d8099a0c756fc16cca06a334430702ae1961665b\
+        4845acd1a8e34101e68bb644ac034dc63e6e344c3d63762a7a5bf59f13095410981d6b6b16bcd4c9\
+        fa68af6e5301ef19bf3a432e406f8567ebd9772e92b5ca5a599671cbfd7ddfa363a536b7ac45f57c\
+        c37d09896fa906972e557180a4ab5ad09d8846dd6da748765436e0160240bd5c921666a1eeaace04\
+        a71b503a1cadf80b3924266c59504f8f215f618b05d54543b6e26d82596fc53b52312c776d12eb2b\
+        659b4fb098df87d683cf9e5412ee56c3fe9841d73fd070dfa51f5bafedf206f13c524e5c50cac990\
+        6efa393290042e3bc59f960b7d240ae443fc49269ce00061e65c6d74812a30dd5f5fe74eff61e0cb\
+        ab3cec75d0aef95083189452dd3d9edf4487bc734c8b24f21296e4e9ef117d7fb977e3b0e6406e63\
+        085906331a93033d1cb8360fe6fea61a6826df36255789f92e40bafcb2ebcb9e556f6c0ccadc6af0\
+        8e31ec4ad5288034e16d155cfdcada7bab599c2fa4ad2e6293f9fe097169148276b6a9eaa72f148b\
+        0c9565c3c2dd63125e0fa530861a710df8e481f2712920f8787e0aedfe618aff50a3b56213884d62\
+        62c11debf2ba7e8ad6692cb17078331418da4be064ff5270073934abcd2ab0469ecaf7275b4bd72b\
+        c6ed34478ea4089b736a16dd906d49f25c33827c571ce0b5d72177aa3508804bc0f8faa947122231\
+        402d2f5cc9a0eb0e09d427b427288d937d9d72b77456f886594cd8c6a462f77fd83076469cc0ecba\
+        3cc40cad69e5b54112eab33396aecfbc211f1f79cf33108e93d95378bae6958274b31088fbd8b3a3\
+        a0d154a789735b0349c4d51c889d08952ddd5488be95560594e673fa051bf9b614a15e100b60a0fe\
+        9a7e12a9b256df589b3e48e5b80fb8cff03e86f60cc070fb23c97d4c14fa3a7346ff556bc6855a5f\
+        83e3dcd9f6eab3dabcd47750e34e7c0938f64d451e39509e902747a70755122095082ab798591907\
+        3141b6d3702091ab717280bdc55e799c01ad8641904e3b1dd29e1a964c737d3c155afb307b748e41\
+        12b48b77d5ed5700e6002b18b0fed2cffdf61fd9934b60732f4d37810a91acef1e038b81d736d98e\
+        ada9cd7e0c2be27ab85032066091224edf872f79637dda3916796a5c62f57f1de37678b6dea00869\
+        933674f88e41a91808073b0f436ebe25a5f44a601033e2184b88db79e968ca6d89b74901be6c6db3\
+        636580182e658dfc686767d6d819fa923e0cdf3ea36576f852bcd4e196a71a1329f6c3ff8e42e309\
+        5abd8ec197990713ee89394c5719b276de8f818a34a7bec1f268682e9142c7d38789f676cc12b71a\
+        b66635c502e69d05b9c7ef01529775c623a48e4cc5c415c9fd565365a4163768785153887fb5f963\
+        e7acc162f2805f45f44487f85e199c1df4a0fca4d44baa62da7af5ed69684112d35f0073732f5a1a\
+        c3e4f021ba5c2c32f06e6b90fae2d254cf09e7690cf4e3aa7030987448e147f943bab5cab558029a\
+        36024d2e790fc6fd667f176e0aa99dd1d72b57",
+        "a71f9689008b1776bf6f79c2a122c42eee420be2d2ab5d25ab39c51e04a44c9c",
+    );
+
+    // Sapling transaction with a Groth16 JoinSplit from the zcashd `TX_READ_WRITE` test.
+    const SAPLING_V4_SPROUT: (&str, &str) = (
+        "0400008085202f89018f642996df1e93a6d79ae5baae3493f423ca6c82e99f3e8d9524fa78bcf161\
+        67000000006b483045022100b65e37229707d9cd483940d2ab8bdc0b74b12dda66d02dbdf36fd383\
+        b9602a5102204be7fd7a39a4a42dff071a5a2bc51b492d33f0bc394bc87861e1bcaaf2bac93b0121\
+        0248e78bdc18f1a83110c12e4008b764026961b168fe8d5a8d947efe6af83cc88effffffff01f0f2\
+        7018020000001976a914a284d0511d0e520d36f444a36c10bf54b4b017cd88ac00000000d7450400\
+        000000000000000000000100ca9a3b0000000000000000000000001331a3059e66aa6ca97a62f56e\
+        a234207568566f6971b3722ae0dd82c00399692aacb5fb12ac580ac26624a8cf0a904cd6f4bfea55\
+        625205cb58f06b1c197423280deac74eea97598c4314d899a4fd85311e046257d2d4c297f1406cf7\
+        09d92a8607f7698d45fe9f41dea3a0571c5da5cfa78e18ebf580c36179d9d6e6320a348f146c407a\
+        dab4cb310392a5f5b5ab283b78343ba91abc7c4bfe23a3dbaf8037c676e595a26574b1813bc2bf2d\
+        2e911f6f3abb0ba6bcac7a2901fbdce65fb07b5636017ef14dff44cdeea730477294f2f8619bd3d5\
+        e6be4898bf8d39c0e0eae5a36864625206b9a8f9940bf16650def7926eb0db43b7d7615e4774cf10\
+        9482f2e807fee6c0c884e8314c67c5d85f4c229cdeab1e964cf0c1adcb47cebfc7c067a0f3c80681\
+        4a285edbb624f4710629098944ac75e7c9cbc56bd0a029e1110eac60cb4077ebf108fe3e67cd0613\
+        91e5d6916d5f41c02b8914c12cf605db7d959226e2e8ff71263b9af4c59b0f4db315b74ca2b0b7d2\
+        5213d5293954c3e51172370fb6c35abe9ce36ef253e3a72e19dac9bd7362c44992974215c82cb90c\
+        99488dbde11963e857cea6b81b8eaae34b7cf5a97d6b60d49fdfa20f5f3c120ef382ca2469604fb0\
+        c6842c6d4fae9661665b5cbc612cef132f88fb7da393f356e3ad13fc3557980a7734231453e44079\
+        042fb432f55e751484d5d6d30fbc4f999013d5d4f2fb62f7144e8dcd2ae59546cc4379ad9f1859ef\
+        80dec66b1a9b0b7fd2c47bd38302d29c31990329a895876ed1d84db757856e75ce9a1dc7c7472bc2\
+        18fb8d7c7d028bb02f10efe7fe6a8c9ce034fea66b909c8d4126251c7d6e54f4cfc778cd4f0e0bad\
+        1096176f2dd45c45cbe15e118f90ff2545f832f23698f2c9531b52655a4c0c8953559928eedfc756\
+        c365cf929b8447dcdc7d823849e02ff68b6278d7542ce0f1070bb1ad913c1a353625f5d35b14cfec\
+        84a633d7fe25256dcffe92f9a6f0fe00caaaa5b39cc2ab06768a42a5b40083cea01c96b3e68d0f6a\
+        587eaf2da6fdadc82527f186a60471ce98e27d2b11efc47998f3030a7a2e5d0b0a7eb80f6bd0e4b9\
+        c8367c6c522d9415f8caec7b0a7318d53dce391cf7e7389c9a74aa6a4c217c288519af81ba2122ca\
+        0c5840cc02cf1bcf150cd3df33c0acfd0053e668b926561b924098d97aaab57ee1113df966a422ef\
+        9b014617bceef05fb6468e330e2dece3f375e98ef03e5b18a953e2301fccec86200ae432c9c12c30\
+        775437f3629714a9fabeb53289402b7fd386cef2b1146723a89d0f81651e00caea2f3ac9eefefb86\
+        8d85ed2354f530fe38fe3a3a6aab47d42dc21329e3ad1b9d06c0c8d6537456f54ad0453f444175d8\
+        7ef5cdd1694662e0a1e6e3632ed7a8e76bc7b1b5a418f086d340815ec398f092e97869f5e201c22c\
+        87918f766a3532eb9a4fc9acf196cbc2d0285119a4216d2581cd2d91bcdce868c468f6f34cf49e3a\
+        56ce249a2fd8cf36b01b0f77de722bbce267e3e5521688e65222235c91c263d80e28297e929d885b\
+        7b9c1a1654b2d0b87577c9a1c725f54415dc5f52dde0695f9f6dcb4b6ee3e3ea702904c11ff92f55\
+        534c7ef98ce793d74756a45d4e320a425e982d5b372d6a8d41fb86ba5164816832a481825c8c6ad7\
+        270969859e55d2367535060f99857065170466bdb70cb93ab2f9c0e293a0a919843bbf34c2fe61b0\
+        c3e32aa7078e83d4c1929e1e1d86141cdeb18920910975db3a7626820599630c423ade233d5d6068\
+        5524e8d8032b861b4aad2002a8fd17c9282b825f02d353e291379ced00ebaa3c03e01d9c59f40509\
+        9d1c3432bad06358d6b1942f0baf710998d10a22d155b0fe849952893126949ff92de3a4c2eeafdf\
+        688435e325d81c2ce008cf6c76030d4d46342ac3372c73986560c4ec35a6f649ef02c11936b7039b\
+        c6f5d09438dbe476251b5964b68f02eedff7a9e0ed3e3090965a22f2c552ce3b2b474fd2fc06b509\
+        27830a05a303faffd68482d7b78538432540dd3261ab759b6582129a7f18d801c54319ca52a3c6a3\
+        db635044d625e24038ad4277f8d5bf016035165f21b070e8169d657d6ed1fa7f8ed09b4e1d9ca2e5\
+        1a24da55e43b3fca9859b2408c26aacbad749ebe882c31e7205e638bb7e2bfc8a3f1c02c0ca7bb9d\
+        aaab7fcbf845d8002c3de79924dcaadc24bdc0082f4a6b61876f3192a881f59a682d273685d4795c\
+        9bd7cccf49de34443a9f9cb35bbf254c50611b7c1324b11094667b6b608c39d1252cebcc4877ceea\
+        76e19b842b67f626743fab297776cc9cf79e90e8fce1001790c2e7d5c958647cca5d3397d20afcf2\
+        9ba44f62a7c62e908d848d81a79fadbb370aba93b03e41d4bc49e299d6d33faf869f36371414ce64\
+        6fc2ca6dcff55a6e0639d50caeb114c418c626b86715436481d1928d55a756a603e7110c3afe963c\
+        2b29a478f9d4397b885a67b093a345796219c111b7e94db390aa4bb76b66a534e5e2679b27db5f95\
+        fd09a36b05",
+        "64f0bd7fe30ce23753358fe3a2dc835b8fba9c0274c4e2c54a6f73114cb55639",
+    );
+
+    // (transaction, txid, auth digest) from the ZIP 244 test vectors.
+    const ZIP244_VECTORS: [(&str, &str, &str); 4] = [
+        (
+            "050000800a27a726b4d0d6c2c2eb518f68984d020100000000000000000000000000000000000000\
+            00000000000000000000000000ffffffff060468984d0200ffffffff00000000",
+            "6427874598e3877e9f28aad8e3c6634e4c7617841b1ab1507a6b8ad73c6cd128",
+            "87d27002f0f4b3e814ae34cafcdc2153d20c01069ee4861e57a223ccb1552133",
+        ),
+        (
+            "050000800a27a726b4d0d6c25e3dbaf7ae12670d0100000000000000000000000000000000000000\
+            00000000000000000000000000ffffffff0604ae12670d00ffffffff01516cf4adec750700036565\
+            00000000",
+            "da900554689a1b1aeed9a12fe1e46302db92f7a0605f4719729bf67ae7eff46b",
+            "03f9779449d90f0ebb7283f652b10f3aa13958d5ea973701a4759914a6fc3b99",
+        ),
+        (
+            "050000800a27a726b4d0d6c2ff6acc0ffc2e490d03146b9d49dd8c7835f43a37dca0787e3ec9f660\
+            5223d5ba7ae0ab9025b73bc03f7fac36c009636363635100635365bca7e54cc1a12d127b57c81389\
+            76e791013b015f06a624f521b6ee04ec980893c7e5e01a3362035904ac000053d7445fe2d09130f6\
+            3511da54832de9136b39f4599f5aa5dfbb45da60cdceab7eefde89be63f3f7c00452006aace1405d\
+            ef0244fd7f99b67d040004630063ac12f6465073e1020009636a5351520065ac65000000",
+            "5837a7f51b79a1bfb6f82d782aaab8718f3465583efbaa44655c1c58cae534bc",
+            "5eeab6d72d8a05bb18615245cb7f2175385b3c03335d8e09e55648fc25bc366c",
+        ),
+        (
+            "050000800a27a726b4d0d6c27a8f739a2d6f2c0201e152a8049e294c4d6e66b164939daffa2ef6ee\
+            6921481cdd86b3cc4318d9614fc820905d0453516aaca3f2498800019f33bf3a109bdd1b232b47b1\
+            646d91e1296634ebde5ccad57288b5b2228186e54b6968912a6381ce3dc166d56a1d62f5a8d7551d\
+            b5fd9313e8c7203d996af7d41a38e01d94903d3c3e0ad3360c1d3710acd20b183e31d49f25c9a138\
+            f49b1a5301466b3da612149df5eda0f14f2efc5c6ac03884428a315dc91f8d7b492ebc57e475a4a6\
+            f26572504b192232ecb9f0c02411e52596bc5e90457e745939ffedbd121e37ec1e9dddc31b06dc95\
+            76a1738ef73e6ba71648913dbf75a779fdd488d83f857deecc40a98d5f2935395ee4762dd21afdbb\
+            5d47fa9a6dd984d567db2857b927b7fae2db587105415d4642789d38f50b8dbcc129cab3d17d19f3\
+            355bcf73cecb8cb8a5da01307152f13936a270572670dc82d39026c6cb4cd4b0f7f5aa2a4f5a5341\
+            ec5dd715406f2fdd2afa733f5f641c8c21862a1bafce2609d9eecfa158cfb5cd79f88008e315dc7d\
+            8388e76c1782fd2795d18a763624c25fa959cc97489ce75745824b77868c53239cfbdf73caec6560\
+            4037314faaceb56218c6bd30f8374ac13386793f21a9fb80ad03bc0cda4a44946c00e1b1a1df0e5b\
+            87b5bece477a709649e950060591394812951e1fe3895b8cc3d14d2cf6556df6ed4b4ddd3d9a69f5\
+            3357d7767f4f5ccbdbc596631277f8fecd08cb056b95e3025b9792fff7f244fc716269b926d62e95\
+            96fa825c6bf21aff9e68625a192440ea06828123d97884806f15fa08da52754a1095e3ff1abd5ce4\
+            fddfccfc3a6128aef784a64610a89d1a7099216d0814d3a2d452431c32d411ac1cce82ad0229407b\
+            bc48985675e3f874a4533f1d63a84dfa3e0f460fe2f57e34fbc75423c3737f5b2a0615f5722db041\
+            a3ef66fa483afd3c2e19e59444a64add6df1d963f5dd5b5010d3d025f0287c4cf19c75f33d51dddd\
+            ba5d657b43ee8da645443814cc7329f3e9b4e54c236c29af3923101756d9fa4bd0f7d2ddaacb6b0f\
+            86a2658e0a07a05ac5b950051cd24c47a88d13d659ba2a46ca1830816d09cd7646f76f716abec5de\
+            07fe9b523410806ea6f288f8736c23357c85f45791e1708029d9824d90704607f387a03e49bf9836\
+            574431345a7877efaa8a08e73081ef8d62cb780ab6883a50a0d470190dfba10a857f82842d3825b3\
+            d6da0573d316eb160dc0b716c48fbd467f75b780149ae8808f4e68f50c0536acddf6f1aeab016b6b\
+            c1a51ed44cfab70000c7b3534201cfb1cd8dbf69b8250c18ef41294ca97993db546c1fe01f7e9c8e\
+            367edcf04be34a9851a7af9db6990ed83dd64af3597c04323ea51b0052ad8084a8b9da948d320dad\
+            d64f5431e61ddf658d24ae67c22c8d1309131fc00fe7f235734276d38d47f1e191e00c7a1d48af04\
+            6827591e9733a97fa6b679f3dc601d008285edcbdae69ce8fc1be4aac00ff2711ebd931de5188568\
+            78f73476f21a482ec9378365c8f7393c94e2885315eb4671098b79535e790fe53e29fef2b3766697\
+            ac32b4f473f468a008e72389fc03880d780cb07fcfaabe3f1a84b27db59a4a153d1070689f2ccf97\
+            5b2b176e1c69dbe381340ef1f98fdc4b453abda3a2bfac3069ba7f1cc50a81c2520e412fab4e5d39\
+            7ecf739f280d5b684533d5d29cfe7e7302ec144b4e553acfd670f77e755fc88e0677e31ba459b44e\
+            307768958fe3789d41c2b1ff434cb30e15914f01bc6bc2307b488d2556d7b7380ea4ffd712f6b02f\
+            e806b94569cd4059f396bf29b99d0a40e5e1711ca944f72d436a102fca4b97693da0b086fe9d2e71\
+            62470d02e0f05d4bec9512bfb3f38327296efaa74328b118c27402c70c3a90b49ad4bbc68e37c0aa\
+            7d9b3fe17799d73b841e751713a02943905aae0803fd69442eb7681ec2a05600054e92eed555028f\
+            21b6a155268a2dd664052528a5f8ed028f59af985ad1315c2e25aeb9d7f134e4bf478642ab96b15d\
+            3b3e13ce2387ac84dc0819e81260e11d392a5f06db8b5633de281a0e9c958c24060297f608af1dc5\
+            1616562b1ffff6e2a28bab1f7772713a0a4b56fe47fb5a7b73aeee5345566ecf3e95e825f92eb469\
+            eb5d69164206a0ea1ce73bfb2a942e73703214d270d80534389b1a1e2bba67481eb3667d6d38254a\
+            c4b44559b4708cdd12898972a895bf0fb055cf1fb9b73029d6bfb27da2b5294f5cb354a894322848\
+            cc3d35b9554a5f62b44a7dcb25406e5ba07882cb6473714e77a051a7dcd29fea0a943785b325cdab\
+            95404fc7aed70525cddb41872cfcc214b13232edc78609753dbff930eb0dc156612b9cb434bc4b69\
+            3392deb87c530435312edcedc6a961133338d786c4a3e103f60110a16b1337129704bf4754ff6ba9\
+            fbe65951e610620f71cda8fc877625f2c5bb04cbe1228b1e886f4050afd8fe94e97d2e9e85c6bb74\
+            8c0042d3249abb1342bb0eebf62058bf3de080d94611a3750915b5dc6c0b3899d41222bace760ee9\
+            c8818ded599e34c56d7372af1eb86852f2a732104bdb750739de6c2c6e0f9eb7cb17f1942bfc9f4f\
+            d6ebb6b4cdd4da2bca26fac4578e9f543405acc7d86ff59158bd0cba3aef6f4a8472d144d99f8b8d\
+            1dedaa9077d4f01d4bb27bbe31d88fbefac3dcd4797563a26b1d61fcd9a464ab21ed550fe6fa0969\
+            5ba0b2f10eea6468cc6e20a66f826e3d14c5006f0563887f5e1289be1b2004caca8d3f34d6e84bf5\
+            9c1e04619a7c23a996941d889e4622a9b9b1d59d5e319094318cd405ba27b7e2c084762d31453ec4\
+            549a4d97729d033460fcf89d6494f2ffd789e98082ea5ce9534b3acd60fe49e37e4f666931677319\
+            ed89f85588741b3128901a93bd78e4be0225a9e2692c77c969ed0176bdf9555948cbd5a332d045de\
+            6ba6bf4490adfe7444cd467a09075417fcc0062e49f008c51ad4227439c1b4476ccd8e97862dab7b\
+            e1e8d399c05ef27c6e22ee273e15786e394c8f1be31682a30147963ac8da8d41d804258426a3f702\
+            89b8ad19d8de13be4eebe3bd4c8a6f55d6e0c373d456851879f5fbc282db9e134806bff71e11bc33\
+            ab75dd6ca067fb73a043b646a7cf39cab4928386786d2f24141ee120fdc34d6764eafc66880ee020\
+            4f53cc1167ed20b43a52dea3ca7cff8ef35cd8e6d7c111a68ef44bcd0c1513ad47ca61c659cc5d32\
+            5b440f6b9f59aff66879bb6688fdb462af43582b983f92b5698b87db46e4b02dd8e81eca555a44f2\
+            f1aef11d88a0bcee76af9ad3f9c46a67062e1a9ca7ea5c014384af07219c7c0ee7fc7bfc7933d174\
+            650f46b4cc000190c19b44c57ae891aa86646c10a177a8626be064409931c37d9e8bdc433b7d79e0\
+            8a12f738a8f0dbddfef2f2657ef3e47d1b0fd11e6a13654db2854fcbff49aa0dadafec320b6ed2d4\
+            b279aee9060c1b221e2eb2f13b0691c4d842406d0ec4282c9526174a09878fe8fdde33a29604e5e5\
+            e7b2a025d6650b97dbb52befb59b1d30a57433b0a351474444099daa371046613260cf3354cfcdad\
+            a663ece824ffd7e44393886a86165ddddf2b4c41773554c86995269408b11e6737a4c447586f6917\
+            3446d8e48bf84cbc000a807899973eb93c5e819aad669413f8387933ad1584aa35e43f4ecd1e2d04\
+            07c0b1b89920ffdfdb9bea51ac95b557af71b89f903f5d9848f14fcbeb1837570f544d6359eb23fa\
+            f38a0822da36ce426c4a2fbeffeb0a8a2e297a9d19ba15024590e3329d9fa9261f9938a4032dd346\
+            06c9cf9f3dd33e576f05cd1dd6811c6298757d77d9e810abdb226afcaa4346a6560f8932b3181fd3\
+            55d5d391976183f8d99388839632d6354f666d09d3e5629ea19737388613d38a34fd0f6e50ee5a0c\
+            c9677177f50028c141378187bd2819403fc534f80076e9380cb4964d3b6b45819d3b8e9caf54f051\
+            852d671bf8c1ffde2d1510756418cb4810936aa57e6965d6fb656a760b7f19adf96c173488552193\
+            b147ee58858033dac7cd0eb204c06490bbdedf5f7571acb2ebe76acef3f2a01ee987486dfe6c3f0a\
+            5e234c127258f97a28fb5d164a8176be946b8097d0e317287f33bf9c16f9a545409ce29b1f427372\
+            5fc0df02a04ebae178b3414fb0a82d50deb09fcf4e6ee9d180ff4f56ff3bc1d3601fc2dc90d814c3\
+            256f4967d3a8d64c83fea339c51f5a8e5801fbb97835581b602465dee04b5922c2761b54245bec0c\
+            9eef2db97d22b2b3556cc969fbb13d06509765a52b3fac54b93f421bf08e18d52ddd52cc1c8ca8ad\
+            faccab7e5cc2f4573fbbf8239bb0b8aedbf8dad16282da5c9125dba1c059d0df8abf621078f02d6c\
+            4bc86d40845ac1d59710c45f07d585eb48b32fc0167ba256e73ca3b9311c62d1094903570519d444\
+            2f0200e6ad11f2452dc9ae85aec01fc56f8cbfda75a7727b75ebbd6bbffb43b63a3b1b871e40feb0\
+            db002974a3c3b1a788567231bf6399ff89236981149d423802d2341a3bedb9ddcbac1fe7b6435e14\
+            79c72e7089d029e7fbbaf3cf37e9b9a6b776791e4c5e6fda57e8d5f14c8c35a2d270846b9dbe005c\
+            da16af4408f3ab06a916eeeb9c9594b70424a4c1d171295b6763b22f47f80b53ccbb904bd68fd65f\
+            bd3fbdea1035e98c21a7dba5fe1089f7d1c032f24d36835aa8815266e897ff829403cfac3a715954\
+            b9b68958a0111a2c9265633ba2831a2e86b941e569d58d99c1383597fad81193c4c13151f40aedb4\
+            87b5c04ae3b1ddfbafa26e720099f26d5a7535aee57306fd2c4f30673cd9b698fecf32faf88f62e2\
+            1c90665859dd26833d21d9bc5452bd19515d3fa5c1e68bc209b9dc2a10ae6b630726a67b33603c69\
+            1fafc281dd94dc9888a68c4f45155aa7897c045aafd9335be2e0ddcf5f586d7f6b4fe12dad9a17f5\
+            db7031",
+            "d0854b7070bb168392e7cf3d3a558711b49c2c0ad8eca3a8a14b8333bd962c55",
+            "57dcab20681fee70a3c653b66322fe3f6158f89ccba1b30f366785675f7e7612",
+        ),
+    ];
+
+    fn round_trip(tx: &str) -> Transaction {
+        let parsed = Transaction::from_hex(tx).unwrap();
+        assert_eq!(parsed.to_hex(), tx);
+        parsed
+    }
+
+    // Every vector but `NO_INPUTS`, whose extensions and prefixes also read as segwit.
+    fn vectors() -> Vec<&'static str> {
+        let mut vectors = vec![
+            GENESIS.0,
+            SEGWIT.0,
+            SPROUT_V2.0,
+            OVERWINTER_V3.0,
+            OVERWINTER_V3_SPROUT.0,
+            SAPLING_V4.0,
+            SAPLING_V4_SPROUT.0,
+        ];
+        vectors.extend(ZIP244_VECTORS.iter().map(|vector| vector.0));
+        vectors
+    }
+
+    #[test]
+    fn bitcoin_transactions() {
+        let genesis = round_trip(GENESIS.0);
+        assert_eq!(genesis.txid(), GENESIS.1);
+        assert_eq!(genesis.wtxid(), GENESIS.1);
+        assert!(genesis.is_coinbase());
+        assert!(genesis.sprout.is_none());
+        assert_eq!(genesis.auth_digest(), None);
+
+        let no_inputs = round_trip(NO_INPUTS.0);
+        assert_eq!(no_inputs.txid(), NO_INPUTS.1);
+        assert!(no_inputs.inputs.is_empty());
+        assert_eq!(no_inputs.outputs.len(), 1);
+
+        let segwit = round_trip(SEGWIT.0);
+        assert!(segwit.has_witness());
+        assert_eq!(segwit.inputs[0].witness.len(), 2);
+        assert_eq!(segwit.txid(), SEGWIT.1);
+        assert_eq!(segwit.wtxid(), SEGWIT.2);
+        assert!(segwit.sprout.is_none());
+    }
+
+    #[test]
+    fn zcash_transactions() {
+        let v2 = round_trip(SPROUT_V2.0);
+        assert_eq!(v2.version, 2);
+        assert!(!v2.is_overwintered());
+        assert_eq!(v2.sprout.as_ref().unwrap().joinsplits.len(), 1);
+        assert_eq!(v2.txid(), SPROUT_V2.1);
+
+        let v3 = round_trip(OVERWINTER_V3.0);
+        assert_eq!(v3.version, 3);
+        assert_eq!(v3.version_group_id, Some(OVERWINTER_VERSION_GROUP_ID));
+        assert!(v3.sprout.is_none());
+        assert_eq!(v3.txid(), OVERWINTER_V3.1);
+
+        let v3 = round_trip(OVERWINTER_V3_SPROUT.0);
+        assert_eq!(v3.sprout.as_ref().unwrap().joinsplits.len(), 1);
+        assert_eq!(v3.txid(), OVERWINTER_V3_SPROUT.1);
+
+        let v4 = round_trip(SAPLING_V4.0);
+        assert_eq!(v4.version, 4);
+        assert_eq!(v4.version_group_id, Some(SAPLING_VERSION_GROUP_ID));
+        let sapling = v4.sapling.as_ref().unwrap();
+        assert_eq!((sapling.spends.len(), sapling.outputs.len()), (2, 1));
+        assert_eq!(v4.txid(), SAPLING_V4.1);
+        assert_eq!(v4.auth_digest(), None);
+
+        let v4 = round_trip(SAPLING_V4_SPROUT.0);
+        assert!(v4.sapling.is_none());
+        assert_eq!(v4.sprout.as_ref().unwrap().joinsplits.len(), 1);
+        assert_eq!(v4.txid(), SAPLING_V4_SPROUT.1);
+    }
+
+    #[test]
+    fn zip244_vectors() {
+        for &(tx, txid, auth_digest) in &ZIP244_VECTORS {
+            let parsed = round_trip(tx);
+            assert_eq!(parsed.version, 5);
+            assert_eq!(parsed.txid(), txid);
+            assert_eq!(parsed.auth_digest().unwrap(), auth_digest);
+        }
+        let shielded = Transaction::from_hex(ZIP244_VECTORS[3].0).unwrap();
+        assert_eq!(shielded.sapling.as_ref().unwrap().spends.len(), 1);
+        assert_eq!(shielded.orchard.as_ref().unwrap().actions.len(), 2);
+    }
+
+    #[test]
+    fn truncated_transactions() {
+        // Prefixes that end right after the lock time of a version 2 transaction are
+        // themselves complete Bitcoin transactions.
+        let complete_prefixes = [(SEGWIT.0, 70), (SPROUT_V2.0, 105)];
+        for tx in vectors() {
+            let data = hex::decode(tx).unwrap();
+            for len in 0..data.len() {
+                if complete_prefixes.contains(&(tx, len)) {
+                    let prefix = Transaction::from_bytes(&data[..len]).unwrap();
+                    assert_eq!(prefix.to_bytes(), &data[..len]);
+                    continue;
+                }
+                assert_eq!(
+                    Transaction::from_bytes(&data[..len]),
+                    Err(TransactionError::UnexpectedEnd)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_data() {
+        for tx in vectors() {
+            let mut data = hex::decode(tx).unwrap();
+            data.extend_from_slice(&[0, 1, 2]);
+            assert_eq!(
+                Transaction::from_bytes(&data),
+                Err(TransactionError::TrailingData(3))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_transactions() {
+        assert_eq!(Transaction::from_hex("0400008"), Err(TransactionError::Hex));
+
+        let mut data = hex::decode(SAPLING_V4.0).unwrap();
+        data[4] ^= 1;
+        assert_eq!(
+            Transaction::from_bytes(&data),
+            Err(TransactionError::UnknownVersionGroup(SAPLING_VERSION_GROUP_ID ^ 1))
+        );
+        data[4] ^= 1;
+        data[0] = 6;
+        assert_eq!(
+            Transaction::from_bytes(&data),
+            Err(TransactionError::UnsupportedVersion(6))
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+
+use exonum_jsonrpc::{Request, Response};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use auth::Credentials;
+use super::{Error, Result, RpcError};
+
+/// Delivers JSON-RPC requests to the node.
+///
+/// Requests are built by `Client`, which also matches response ids and maps errors;
+/// implement this to add TLS, proxies, custom headers or a mock. The `Request` and
+/// `Response` types are re-exported from the crate root.
+pub trait Transport {
+    fn send_request(&self, request: &Request) -> Result<Response>;
+
+    /// Sends requests in a single round trip; responses may come in any order.
+    ///
+    /// The default implementation sends them one by one.
+    fn send_batch(&self, requests: &[Request]) -> Result<Vec<Response>> {
+        requests
+            .iter()
+            .map(|request| self.send_request(request))
+            .collect()
+    }
+}
+
+/// JSON-RPC over HTTP with basic authentication, the default transport.
+///
+/// Posts requests like the client of `exonum_jsonrpc`, which cannot send batches,
+/// follow a rotated cookie or report the HTTP status. Responses with an error status
+/// and no JSON-RPC body fail with `Error::Unauthorized` or `Error::Http`.
+pub struct HttpTransport {
+    url: String,
+    credentials: Credentials,
+    http: reqwest::Client,
+}
+
+impl HttpTransport {
+    pub fn new<S>(url: S, user: Option<String>, password: Option<String>) -> HttpTransport
+    where
+        S: Into<String>,
+    {
+        HttpTransport::with_credentials(url, Credentials::new(user, password))
+    }
+
+    pub(crate) fn with_credentials<S>(url: S, credentials: Credentials) -> HttpTransport
+    where
+        S: Into<String>,
+    {
+        HttpTransport {
+            url: url.into(),
+            credentials,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Password the transport was created with; for cookie authentication, the
+    /// cookie read at creation.
+    pub fn password(&self) -> &Option<String> {
+        self.credentials.password()
+    }
+
+    pub fn username(&self) -> &Option<String> {
+        self.credentials.user()
+    }
+
+    // Posts `body`, re-reading a rotated cookie once if the node rejects the credentials.
+    fn post<B, R>(&self, body: &B) -> Result<R>
+    where
+        B: Serialize,
+        R: DeserializeOwned,
+    {
+        let mut response = self.send(body)?;
+        if response.status() == StatusCode::UNAUTHORIZED && self.credentials.reload_cookie() {
+            response = self.send(body)?;
+        }
+        let mut content = Vec::new();
+        response.copy_to(&mut content).map_err(RpcError::from)?;
+        parse_response(response.status(), &content)
+    }
+
+    fn send<B: Serialize>(&self, body: &B) -> Result<reqwest::Response> {
+        let mut builder = self.http.post(&self.url);
+        if let Some((user, password)) = self.credentials.basic_auth() {
+            builder = builder.basic_auth(user, password);
+        }
+        Ok(builder.json(body).send().map_err(RpcError::from)?)
+    }
+}
+
+// Parses a node response. The node reports RPC errors with error statuses too, so only
+// responses without a JSON-RPC body are HTTP failures.
+pub(crate) fn parse_response<R: DeserializeOwned>(status: StatusCode, body: &[u8]) -> Result<R> {
+    match serde_json::from_slice(body) {
+        Ok(response) => Ok(response),
+        Err(_) if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN => {
+            Err(Error::Unauthorized(status.as_u16()))
+        }
+        Err(_) if !status.is_success() => Err(Error::Http(status.as_u16())),
+        Err(e) => Err(RpcError::Json(e).into()),
+    }
+}
+
+impl fmt::Debug for HttpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HttpTransport").field("url", &self.url).finish()
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send_request(&self, request: &Request) -> Result<Response> {
+        let response: Response = self.post(request)?;
+        if response.id != request.id {
+            return Err(RpcError::NonceMismatch.into());
+        }
+        Ok(response)
+    }
+
+    fn send_batch(&self, requests: &[Request]) -> Result<Vec<Response>> {
+        self.post(&requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum_jsonrpc::{Request, Response};
+    use serde_json::{self, Value};
+
+    use super::{HttpTransport, Transport};
+    use mock::{MockNode, MockServer};
+    use {Client, Error, Result, RpcError};
+
+    fn request(method: &str, id: u64) -> Request {
+        Request {
+            method: method.to_owned(),
+            params: Vec::new(),
+            id: json!(id),
+        }
+    }
+
+    // Answers every request with `status` and `response`, with the id of the request.
+    fn answering(status: u16, response: Value) -> MockServer {
+        MockServer::start(move |request| {
+            let request: Value = serde_json::from_slice(&request.body)?;
+            let mut response = response.clone();
+            if response.is_object() {
+                response["id"] = request["id"].clone();
+            }
+            Ok((status, serde_json::to_vec(&response)?))
+        }).unwrap()
+    }
+
+    fn getblockcount(server: &MockServer) -> Result<u64> {
+        Client::new(server.url(), Some("user".into()), Some("password".into())).getblockcount()
+    }
+
+    #[test]
+    fn http_errors_without_rpc_response() {
+        for &status in &[401, 403] {
+            let server = MockServer::start(move |_| Ok((status, Vec::new()))).unwrap();
+            assert!(matches!(getblockcount(&server), Err(Error::Unauthorized(s)) if s == status));
+        }
+
+        let server = MockServer::start(|_| Ok((503, b"<html>Overloaded</html>".to_vec()))).unwrap();
+        let error = getblockcount(&server).unwrap_err();
+        assert!(matches!(error, Error::Http(503)));
+        assert!(error.is_retryable());
+
+        let server = MockServer::start(|_| Ok((200, b"<html>".to_vec()))).unwrap();
+        assert!(matches!(getblockcount(&server), Err(Error::Rpc(RpcError::Json(_)))));
+    }
+
+    #[test]
+    fn rpc_errors_with_error_status() {
+        let error = json!({ "result": null, "error": { "code": -8, "message": "Bad height" } });
+        let server = answering(500, error);
+        let error = getblockcount(&server).unwrap_err();
+        assert!(matches!(error, Error::InvalidParameter(_)));
+        assert_eq!(error.message(), Some("Bad height"));
+
+        let server = answering(200, json!({ "result": 7, "error": null }));
+        assert_eq!(getblockcount(&server).unwrap(), 7);
+    }
+
+    #[test]
+    fn send_request_checks_ids() {
+        let node = MockNode::new();
+        node.respond("getblockcount", json!(7));
+        let server = node.start().unwrap();
+        let transport = HttpTransport::new(server.url(), None, None);
+        let response = transport.send_request(&request("getblockcount", 3)).unwrap();
+        assert_eq!(response.id, json!(3));
+        assert_eq!(response.result, Some(json!(7)));
+
+        let server = MockServer::start(|_| {
+            Ok((200, br#"{"result":7,"error":null,"id":99}"#.to_vec()))
+        }).unwrap();
+        let transport = HttpTransport::new(server.url(), None, None);
+        assert!(matches!(
+            transport.send_request(&request("getblockcount", 3)),
+            Err(Error::Rpc(RpcError::NonceMismatch))
+        ));
+        assert!(matches!(getblockcount(&server), Err(Error::Rpc(RpcError::NonceMismatch))));
+    }
+
+    #[test]
+    fn send_batch_posts_all_requests_at_once() {
+        // Answers in reverse order, which the transport passes on as is.
+        let server = MockServer::start(|request| {
+            let requests: Vec<Request> = serde_json::from_slice(&request.body)?;
+            let responses: Vec<Response> = requests
+                .iter()
+                .rev()
+                .map(|request| Response {
+                    result: Some(json!(request.method)),
+                    error: None,
+                    id: request.id.clone(),
+                })
+                .collect();
+            Ok((200, serde_json::to_vec(&responses)?))
+        }).unwrap();
+        let transport = HttpTransport::new(server.url(), None, None);
+        let requests = [request("getblockcount", 1), request("getbestblockhash", 2)];
+        let responses = transport.send_batch(&requests).unwrap();
+        let ids: Vec<_> = responses.iter().map(|response| response.id.clone()).collect();
+        assert_eq!(ids, vec![json!(2), json!(1)]);
+        assert_eq!(responses[0].result, Some(json!("getbestblockhash")));
+    }
+
+    // Relies on the default `send_batch`.
+    struct Sequential(MockNode);
+
+    impl Transport for Sequential {
+        fn send_request(&self, request: &Request) -> Result<Response> {
+            self.0.send_request(request)
+        }
+    }
+
+    #[test]
+    fn default_send_batch_sends_requests_one_by_one() {
+        let node = MockNode::new();
+        node.respond("getblockcount", json!(7));
+        node.respond("getbestblockhash", json!("00ff"));
+        let transport = Sequential(node.clone());
+
+        let requests = [request("getblockcount", 1), request("getbestblockhash", 2)];
+        let responses = transport.send_batch(&requests).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].result, Some(json!(7)));
+        assert_eq!(responses[1].id, json!(2));
+        let methods: Vec<_> = node.calls().into_iter().map(|call| call.method).collect();
+        assert_eq!(methods, vec!["getblockcount", "getbestblockhash"]);
+
+        let client = Client::with_transport(transport);
+        let mut batch = client.batch();
+        let hash = batch.call::<String>("getbestblockhash", vec![]);
+        let count = batch.call::<u64>("getblockcount", vec![]);
+        let mut responses = batch.send().unwrap();
+        assert_eq!(responses.take(count).unwrap(), 7);
+        assert_eq!(responses.take(hash).unwrap(), "00ff");
+    }
+}
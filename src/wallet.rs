@@ -0,0 +1,122 @@
+use std::ops::Deref;
+use std::time::Duration;
+
+use super::{Client, HttpTransport, Result, SecretString, Transport};
+
+/// Wallet unlocked by `Client::unlock`, locked again when dropped.
+///
+/// Dereferences to the client, so wallet calls can be made through the guard.
+#[derive(Debug)]
+pub struct UnlockedWallet<'a, T: Transport + 'a = HttpTransport> {
+    client: &'a Client<T>,
+}
+
+impl<'a, T: Transport> Deref for UnlockedWallet<'a, T> {
+    type Target = Client<T>;
+
+    fn deref(&self) -> &Client<T> {
+        self.client
+    }
+}
+
+impl<'a, T: Transport> Drop for UnlockedWallet<'a, T> {
+    fn drop(&mut self) {
+        if let Err(e) = self.client.walletlock() {
+            warn!("Unable to lock the wallet: {}", e);
+        }
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Unlocks the wallet until the returned guard is dropped, or at most for `timeout`.
+    pub fn unlock(&self, passphrase: &SecretString, timeout: Duration) -> Result<UnlockedWallet<'_, T>> {
+        self.walletpassphrase(passphrase, timeout)?;
+        Ok(UnlockedWallet { client: self })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::Value;
+
+    use mock::MockNode;
+    use {Error, Result, SecretString};
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn node() -> MockNode {
+        let node = MockNode::new();
+        node.respond("walletpassphrase", Value::Null);
+        node.respond("walletlock", Value::Null);
+        node
+    }
+
+    #[test]
+    fn locks_the_wallet_when_dropped() {
+        let node = node();
+        node.respond("dumpprivkey", json!("key"));
+        let client = node.client();
+        let passphrase = SecretString::from("passphrase");
+        {
+            let wallet = client.unlock(&passphrase, MINUTE).unwrap();
+            let unlocks = node.calls_to("walletpassphrase");
+            assert_eq!(unlocks, vec![vec![json!("passphrase"), json!(60)]]);
+            assert_eq!(wallet.dumpprivkey("t1address").unwrap(), "key");
+            assert!(node.calls_to("walletlock").is_empty());
+        }
+        assert_eq!(node.calls_to("walletlock"), vec![Vec::<Value>::new()]);
+    }
+
+    #[test]
+    fn locks_the_wallet_after_errors() {
+        let node = node();
+        node.fail("dumpprivkey", -5, "Invalid Zcash address");
+        let client = node.client();
+        let result = (|| -> Result<String> {
+            let wallet = client.unlock(&SecretString::from("passphrase"), MINUTE)?;
+            wallet.dumpprivkey("t1address")
+        })();
+        match result {
+            Err(Error::NoInformation(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(node.calls_to("walletlock").len(), 1);
+    }
+
+    #[test]
+    fn failed_unlock_does_not_lock() {
+        let node = node();
+        node.fail("walletpassphrase", -14, "The wallet passphrase entered was incorrect.");
+        let client = node.client();
+        match client.unlock(&SecretString::from("wrong"), MINUTE) {
+            Err(Error::WalletPassphraseIncorrect(_)) => {}
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+        assert!(node.calls_to("walletlock").is_empty());
+    }
+
+    #[test]
+    fn maps_wallet_errors() {
+        let node = node();
+        node.fail("dumpprivkey", -13, "Please enter the wallet passphrase first.");
+        node.fail("walletlock", -15, "Running with an unencrypted wallet.");
+        node.fail("encryptwallet", -15, "Wallet is already encrypted.");
+        let client = node.client();
+        match client.dumpprivkey("t1address") {
+            Err(Error::WalletUnlockNeeded(ref msg)) => {
+                assert_eq!(msg, "Please enter the wallet passphrase first.")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match client.walletlock() {
+            Err(Error::WalletWrongEncState(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        match client.encryptwallet(&SecretString::from("passphrase")) {
+            Err(Error::WalletWrongEncState(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}